@@ -1,37 +1,181 @@
-use magnus::{function, prelude::*, Error, Value, RArray, Integer};
-use ndarray::{Array1, Array2, ArrayView1, Axis};
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use ndarray::{Array2, ArrayView1, ArrayViewMut1, Axis};
 use rand::prelude::*;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use crate::utils::{ruby_array_to_ndarray2};
+use rayon::prelude::*;
+use crate::errors::dimension_mismatch_error;
+use crate::utils::ruby_array_to_ndarray;
 
+mod agglomerative;
+mod comparison;
+mod dbscan;
+mod elkan;
+mod gmm;
+mod hdbscan_hnsw;
+mod hdbscan_tree;
 mod hdbscan_wrapper;
+mod kmedoids;
+mod knn;
+mod linkage;
+mod metric;
+mod minibatch_kmeans;
+mod model_selection;
+mod neighbors;
+mod optics;
+mod spectral;
+mod validity;
+
+use elkan::ElkanBounds;
+
+// Bounds on the chunks used to accumulate centroid sums in parallel
+const ACCUMULATE_MIN_CHUNK: usize = 4096;
+const ACCUMULATE_MAX_CHUNKS: usize = 64;
 
 pub fn init(parent: &magnus::RModule) -> Result<(), Error> {
     let clustering_module = parent.define_module("Clustering")?;
     
     clustering_module.define_singleton_method(
         "kmeans_rust",
-        function!(kmeans, 4),
+        function!(kmeans, 10),
     )?;
     
     clustering_module.define_singleton_method(
         "kmeans_predict_rust",
-        function!(kmeans_predict, 2),
+        function!(kmeans_predict, 3),
     )?;
     
+    // Initialize mini-batch k-means functions
+    minibatch_kmeans::init(&clustering_module)?;
+    
+    // Initialize k-medoids functions
+    kmedoids::init(&clustering_module)?;
+    
+    // Initialize k selection (elbow, gap statistic, silhouette)
+    model_selection::init(&clustering_module)?;
+    
+    // Initialize cluster validity metrics
+    validity::init(&clustering_module)?;
+    
+    // Initialize clustering comparison metrics
+    comparison::init(&clustering_module)?;
+    
     // Initialize HDBSCAN functions
     hdbscan_wrapper::init(&clustering_module)?;
     
+    // Initialize DBSCAN and OPTICS functions
+    dbscan::init(&clustering_module)?;
+    optics::init(&clustering_module)?;
+    
+    // Initialize agglomerative clustering functions
+    agglomerative::init(&clustering_module)?;
+    
+    // Initialize Gaussian mixture functions
+    gmm::init(&clustering_module)?;
+    
+    // Initialize spectral clustering functions
+    spectral::init(&clustering_module)?;
+    
     Ok(())
 }
 
+/// Assignment strategy for k-means iterations
+#[derive(Clone, Copy)]
+enum KMeansAlgorithm {
+    /// Compare every point against every centroid
+    Lloyd,
+    /// Skip comparisons ruled out by triangle-inequality bounds
+    Elkan,
+}
+
+/// What to do with a centroid that loses all of its points
+#[derive(Clone, Copy)]
+enum EmptyClusterPolicy {
+    /// Move it to the point contributing most to the inertia
+    Relocate,
+    /// Leave it where it is
+    Keep,
+}
+
+/// Distance used to assign points to centroids
+#[derive(Clone, Copy)]
+enum KMeansMetric {
+    /// Squared Euclidean distance with mean centroids
+    Euclidean,
+    /// Spherical k-means: points and centroids are unit-normalized, so the
+    /// squared Euclidean distance ranks centroids by cosine similarity
+    Cosine,
+    /// L1 distance with coordinate-wise median centroids (k-medians)
+    Manhattan,
+}
+
+impl KMeansMetric {
+    fn parse(metric: &str) -> Result<Self, Error> {
+        match metric {
+            "euclidean" => Ok(KMeansMetric::Euclidean),
+            "cosine" => Ok(KMeansMetric::Cosine),
+            "manhattan" => Ok(KMeansMetric::Manhattan),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown k-means metric: {} (expected euclidean, cosine or manhattan)", other),
+            )),
+        }
+    }
+    
+    /// Distance the centroid update minimises
+    fn distance(self, a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
+        match self {
+            KMeansMetric::Euclidean | KMeansMetric::Cosine => squared_euclidean_distance(a, b),
+            KMeansMetric::Manhattan => a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum(),
+        }
+    }
+}
+
+/// Settings shared by every run of a k-means fit
+struct KMeansParams {
+    k: usize,
+    max_iter: usize,
+    // Absolute tolerance on the total squared centroid shift
+    tol: f64,
+    algorithm: KMeansAlgorithm,
+    empty_cluster: EmptyClusterPolicy,
+    metric: KMeansMetric,
+}
+
+/// Result of a single k-means run
+struct KMeansRun {
+    labels: Vec<usize>,
+    centroids: Array2<f64>,
+    inertia: f64,
+    n_iter: usize,
+    converged: bool,
+    n_relocations: usize,
+}
+
 /// Perform K-means clustering
-/// Returns (labels, centroids, inertia)
-fn kmeans(data: Value, k: usize, max_iter: usize, random_seed: Option<i64>) -> Result<(RArray, RArray, f64), Error> {
+/// Runs `n_init` k-means++ initializations in parallel and keeps the lowest inertia
+/// `algorithm` is "lloyd" or "elkan"; both produce identical results
+/// `empty_cluster` is "relocate" or "keep"
+/// `sample_weight` optionally weights each point in seeding, centroid updates and inertia
+/// `metric` is "euclidean", "cosine" or "manhattan"
+/// Returns a hash with labels, centroids, inertia, n_iter, converged and n_relocations
+#[allow(clippy::too_many_arguments)]
+fn kmeans(
+    data: Value,
+    k: usize,
+    max_iter: usize,
+    random_seed: Option<i64>,
+    n_init: usize,
+    tol: f64,
+    algorithm: String,
+    empty_cluster: String,
+    sample_weight: Option<Vec<f64>>,
+    metric: String,
+) -> Result<RHash, Error> {
     // Convert Ruby array to ndarray using shared helper
-    let data_array = ruby_array_to_ndarray2(data)?;
+    let mut data_array = ruby_array_to_ndarray::<f64>(data)?;
     let (n_samples, n_features) = data_array.dim();
+    let sample_weight = validate_sample_weight(sample_weight, n_samples)?;
     
     if k > n_samples {
         return Err(Error::new(
@@ -40,120 +184,424 @@ fn kmeans(data: Value, k: usize, max_iter: usize, random_seed: Option<i64>) -> R
         ));
     }
     
+    if n_init == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "n_init must be at least 1",
+        ));
+    }
+    
+    if tol.is_nan() || tol < 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("tol must be non-negative (got {})", tol),
+        ));
+    }
+    
+    let algorithm = match algorithm.as_str() {
+        "lloyd" => KMeansAlgorithm::Lloyd,
+        "elkan" => KMeansAlgorithm::Elkan,
+        other => {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown k-means algorithm: {} (expected lloyd or elkan)", other),
+            ));
+        }
+    };
+    
+    let empty_cluster = match empty_cluster.as_str() {
+        "relocate" => EmptyClusterPolicy::Relocate,
+        "keep" => EmptyClusterPolicy::Keep,
+        other => {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown empty cluster policy: {} (expected relocate or keep)", other),
+            ));
+        }
+    };
+    
+    let metric = KMeansMetric::parse(&metric)?;
+    if let (KMeansAlgorithm::Elkan, KMeansMetric::Manhattan) = (algorithm, metric) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "The elkan algorithm requires the euclidean or cosine metric",
+        ));
+    }
+    
+    // Spherical k-means works on the unit sphere throughout
+    if let KMeansMetric::Cosine = metric {
+        normalize_rows(&mut data_array);
+    }
+    
+    // tol is relative to the data: scale it by the mean per-feature variance
+    let params = KMeansParams {
+        k,
+        max_iter,
+        tol: tol * data_array.var_axis(Axis(0), 0.0).mean().unwrap_or(0.0),
+        algorithm,
+        empty_cluster,
+        metric,
+    };
+    
+    let best = kmeans_best_of(&data_array, sample_weight.as_deref(), &params, random_seed, n_init);
+    
+    // Convert results to Ruby arrays
+    let labels_array = RArray::with_capacity(n_samples);
+    for label in best.labels {
+        labels_array.push(label)?;
+    }
+    
+    let centroids_array = RArray::new();
+    for i in 0..k {
+        let row_array = RArray::new();
+        for j in 0..n_features {
+            row_array.push(best.centroids[[i, j]])?;
+        }
+        centroids_array.push(row_array)?;
+    }
+    
+    let result = RHash::new();
+    result.aset("labels", labels_array)?;
+    result.aset("centroids", centroids_array)?;
+    result.aset("inertia", best.inertia)?;
+    result.aset("n_iter", best.n_iter)?;
+    result.aset("converged", best.converged)?;
+    result.aset("n_relocations", best.n_relocations)?;
+    
+    Ok(result)
+}
+
+/// Run `n_init` k-means++ initializations in parallel and keep the lowest inertia
+fn kmeans_best_of(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    params: &KMeansParams,
+    random_seed: Option<i64>,
+    n_init: usize,
+) -> KMeansRun {
+    // Each run gets its own seed derived from random_seed, so the first run
+    // matches a single-initialization fit and the whole search is reproducible
+    let runs: Vec<KMeansRun> = (0..n_init)
+        .into_par_iter()
+        .map(|run| {
+            let run_seed = random_seed.map(|seed| seed.wrapping_add(run as i64));
+            kmeans_single(data, weights, params, run_seed)
+        })
+        .collect();
+    
+    // Keep the lowest inertia; ties go to the earliest run
+    runs.into_iter()
+        .reduce(|best, run| if run.inertia < best.inertia { run } else { best })
+        .expect("n_init is at least 1")
+}
+
+/// Check that sample weights match the data and are finite and non-negative
+/// with a positive total
+fn validate_sample_weight(sample_weight: Option<Vec<f64>>, n_samples: usize) -> Result<Option<Vec<f64>>, Error> {
+    let Some(weights) = sample_weight else {
+        return Ok(None);
+    };
+    
+    if weights.len() != n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("sample_weight has {} entries but data has {} samples", weights.len(), n_samples),
+        ));
+    }
+    
+    if let Some(i) = weights.iter().position(|w| !w.is_finite() || *w < 0.0) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("sample_weight must be finite and non-negative (got {} at index {})", weights[i], i),
+        ));
+    }
+    
+    if weights.iter().sum::<f64>() <= 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "sample_weight must have a positive sum",
+        ));
+    }
+    
+    Ok(Some(weights))
+}
+
+/// Run k-means from a single k-means++ initialization
+/// Stops when labels no longer change, when the total squared centroid shift
+/// drops to `tol` or after `max_iter` iterations
+fn kmeans_single(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    params: &KMeansParams,
+    random_seed: Option<i64>,
+) -> KMeansRun {
+    let n_samples = data.nrows();
+    let k = params.k;
+    
     // Initialize centroids using K-means++
-    let mut centroids = kmeans_plusplus(&data_array, k, random_seed)?;
+    let mut centroids = kmeans_plusplus(data, weights, params.metric, k, random_seed);
     let mut labels = vec![0usize; n_samples];
-    let mut prev_labels = vec![0usize; n_samples];
+    let mut bounds: Option<ElkanBounds> = None;
+    let mut n_iter = 0;
+    let mut converged = false;
+    let mut labels_current = false;
+    let mut n_relocations = 0;
     
     // K-means iterations
-    for iteration in 0..max_iter {
-        // Assign points to nearest centroid
-        let mut changed = false;
-        for i in 0..n_samples {
-            let point = data_array.row(i);
-            let mut min_dist = f64::INFINITY;
-            let mut best_cluster = 0;
-            
-            for (j, centroid) in centroids.axis_iter(Axis(0)).enumerate() {
-                let dist = euclidean_distance(&point, &centroid);
-                if dist < min_dist {
-                    min_dist = dist;
-                    best_cluster = j;
+    for iteration in 0..params.max_iter {
+        n_iter = iteration + 1;
+        
+        // Assign points to nearest centroid (in parallel)
+        let changed = match (params.algorithm, bounds.as_mut()) {
+            (KMeansAlgorithm::Lloyd, _) => {
+                let assignments = assign_labels(data, &centroids, params.metric);
+                let mut changed = false;
+                for (label, &(best_cluster, _)) in labels.iter_mut().zip(assignments.iter()) {
+                    if *label != best_cluster {
+                        changed = true;
+                    }
+                    *label = best_cluster;
                 }
+                changed
             }
-            
-            if labels[i] != best_cluster {
-                changed = true;
+            (KMeansAlgorithm::Elkan, Some(bounds)) => bounds.assign(data, &centroids, &mut labels),
+            (KMeansAlgorithm::Elkan, None) => {
+                let (initial_bounds, initial_labels) = ElkanBounds::new(data, &centroids);
+                bounds = Some(initial_bounds);
+                labels = initial_labels;
+                true
             }
-            labels[i] = best_cluster;
-        }
+        };
         
         // Check for convergence
         if !changed && iteration > 0 {
+            converged = true;
+            labels_current = true;
             break;
         }
         
-        // Update centroids
+        // Update centroids from per-cluster sums accumulated in parallel
+        let (mut sums, mut counts) = accumulate_clusters(data, weights, &labels, k);
+        if let EmptyClusterPolicy::Relocate = params.empty_cluster {
+            n_relocations += relocate_empty_clusters(
+                data, weights, &centroids, params.metric, &mut labels, &mut sums, &mut counts,
+            );
+        }
+        let mut new_centroids = match params.metric {
+            KMeansMetric::Euclidean | KMeansMetric::Cosine => sums,
+            KMeansMetric::Manhattan => cluster_medians(data, weights, &labels, k),
+        };
+        let mut shifts = vec![0.0; k];
         for j in 0..k {
-            let mut sum = Array1::<f64>::zeros(n_features);
-            let mut count = 0;
-            
-            for i in 0..n_samples {
-                if labels[i] == j {
-                    sum += &data_array.row(i);
-                    count += 1;
+            if counts[j] > 0.0 {
+                let mut new_centroid = new_centroids.row_mut(j);
+                match params.metric {
+                    KMeansMetric::Euclidean => new_centroid /= counts[j],
+                    KMeansMetric::Cosine => normalize(&mut new_centroid),
+                    KMeansMetric::Manhattan => {}
                 }
+                shifts[j] = squared_euclidean_distance(&centroids.row(j), &new_centroid.view());
+                centroids.row_mut(j).assign(&new_centroid);
             }
-            
-            if count > 0 {
-                centroids.row_mut(j).assign(&(sum / count as f64));
-            }
+        }
+        let shift: f64 = shifts.iter().sum();
+        
+        if let Some(bounds) = bounds.as_mut() {
+            let distances: Vec<f64> = shifts.iter().map(|shift| shift.sqrt()).collect();
+            bounds.update(&labels, &distances);
         }
         
-        prev_labels.clone_from(&labels);
+        if shift <= params.tol {
+            converged = true;
+            break;
+        }
     }
     
-    // Calculate inertia (sum of squared distances to nearest centroid)
-    let mut inertia = 0.0;
-    for i in 0..n_samples {
-        let point = data_array.row(i);
-        let centroid = centroids.row(labels[i]);
-        inertia += euclidean_distance(&point, &centroid).powi(2);
+    // Make the labels consistent with the final centroids
+    if !labels_current {
+        labels = assign_labels(data, &centroids, params.metric).into_iter().map(|(label, _)| label).collect();
     }
     
-    // Convert results to Ruby arrays
-    let ruby = magnus::Ruby::get().unwrap();
-    let labels_array = RArray::new();
-    for label in labels {
-        labels_array.push(Integer::from_value(ruby.eval(&format!("{}", label)).unwrap()).unwrap())?;
+    // Calculate inertia (weighted sum of distances to nearest centroid)
+    // Distances are computed in parallel but summed in order for reproducibility
+    let mut inertia: f64 = weighted_distances(data, weights, &centroids, params.metric, &labels).iter().sum();
+    if let KMeansMetric::Cosine = params.metric {
+        // Between unit vectors, half the squared distance is the cosine distance
+        inertia *= 0.5;
     }
     
-    let centroids_array = RArray::new();
-    for i in 0..k {
-        let row_array = RArray::new();
-        for j in 0..n_features {
-            row_array.push(centroids[[i, j]])?;
-        }
-        centroids_array.push(row_array)?;
+    KMeansRun { labels, centroids, inertia, n_iter, converged, n_relocations }
+}
+
+/// Distance from each point to its assigned centroid, scaled by its weight
+fn weighted_distances(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    centroids: &Array2<f64>,
+    metric: KMeansMetric,
+    labels: &[usize],
+) -> Vec<f64> {
+    labels
+        .par_iter()
+        .enumerate()
+        .map(|(i, &label)| {
+            let dist = metric.distance(&data.row(i), &centroids.row(label));
+            match weights {
+                Some(weights) => weights[i] * dist,
+                None => dist,
+            }
+        })
+        .collect()
+}
+
+/// Give each empty cluster the point contributing most to the inertia
+/// Returns the number of clusters relocated
+fn relocate_empty_clusters(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    centroids: &Array2<f64>,
+    metric: KMeansMetric,
+    labels: &mut [usize],
+    sums: &mut Array2<f64>,
+    counts: &mut [f64],
+) -> usize {
+    let empty: Vec<usize> = (0..counts.len()).filter(|&j| counts[j] == 0.0).collect();
+    relocate_clusters(empty, data, weights, centroids, metric, labels, sums, counts)
+}
+
+/// Give each of the `empty` clusters the point contributing most to the inertia
+/// The point is moved out of its cluster's sums so the centroid update lands
+/// exactly on it. Points whose removal would empty their own cluster, or that
+/// contribute nothing, are never taken. Returns the number of clusters relocated
+#[allow(clippy::too_many_arguments)]
+fn relocate_clusters(
+    empty: Vec<usize>,
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    centroids: &Array2<f64>,
+    metric: KMeansMetric,
+    labels: &mut [usize],
+    sums: &mut Array2<f64>,
+    counts: &mut [f64],
+) -> usize {
+    if empty.is_empty() {
+        return 0;
     }
     
-    Ok((labels_array, centroids_array, inertia))
+    let distances = weighted_distances(data, weights, centroids, metric, labels);
+    let weight = |i: usize| weights.map_or(1.0, |weights| weights[i]);
+    
+    // Farthest first; ties go to the lowest index
+    let mut candidates: Vec<usize> = (0..distances.len()).filter(|&i| distances[i] > 0.0).collect();
+    candidates.sort_by(|&a, &b| distances[b].total_cmp(&distances[a]).then(a.cmp(&b)));
+    
+    let mut candidates = candidates.into_iter();
+    let mut relocated = 0;
+    for j in empty {
+        let Some(point) = candidates.by_ref().find(|&i| counts[labels[i]] > weight(i)) else {
+            break;
+        };
+        
+        let old = labels[point];
+        let point_weight = weight(point);
+        sums.row_mut(old).scaled_add(-point_weight, &data.row(point));
+        counts[old] -= point_weight;
+        
+        sums.row_mut(j).assign(&(&data.row(point) * point_weight));
+        counts[j] = point_weight;
+        labels[point] = j;
+        relocated += 1;
+    }
+    
+    relocated
+}
+
+/// Weighted coordinate-wise median of the points in each cluster
+/// This is the k-medians update, minimising the L1 distance to the members;
+/// rows of empty clusters are left at zero
+fn cluster_medians(data: &Array2<f64>, weights: Option<&[f64]>, labels: &[usize], k: usize) -> Array2<f64> {
+    let n_features = data.ncols();
+    let weight = |i: usize| weights.map_or(1.0, |weights| weights[i]);
+    
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); k];
+    for (i, &label) in labels.iter().enumerate() {
+        members[label].push(i);
+    }
+    
+    let rows: Vec<Vec<f64>> = members
+        .par_iter()
+        .map(|indices| {
+            (0..n_features)
+                .map(|feature| {
+                    let mut values: Vec<(f64, f64)> = indices
+                        .iter()
+                        .map(|&i| (data[[i, feature]], weight(i)))
+                        .collect();
+                    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+                    
+                    let half = values.iter().map(|&(_, w)| w).sum::<f64>() / 2.0;
+                    let mut cumulative = 0.0;
+                    for &(value, w) in &values {
+                        cumulative += w;
+                        if cumulative >= half {
+                            return value;
+                        }
+                    }
+                    0.0
+                })
+                .collect()
+        })
+        .collect();
+    
+    let mut medians = Array2::<f64>::zeros((k, n_features));
+    for (j, row) in rows.into_iter().enumerate() {
+        for (feature, value) in row.into_iter().enumerate() {
+            medians[[j, feature]] = value;
+        }
+    }
+    medians
 }
 
 /// Predict cluster labels for new data given centroids
-fn kmeans_predict(data: Value, centroids: Value) -> Result<RArray, Error> {
+/// `metric` must match the one the centroids were fitted with
+fn kmeans_predict(data: Value, centroids: Value, metric: String) -> Result<RArray, Error> {
     // Convert inputs using shared helpers
-    let data_matrix = ruby_array_to_ndarray2(data)?;
-    let centroids_matrix = ruby_array_to_ndarray2(centroids)?;
+    let mut data_matrix = ruby_array_to_ndarray::<f64>(data)?;
+    let mut centroids_matrix = ruby_array_to_ndarray::<f64>(centroids)?;
+    let metric = KMeansMetric::parse(&metric)?;
     
-    let (n_samples, _) = data_matrix.dim();
-    let (_k, _) = centroids_matrix.dim();
+    let (n_samples, n_features) = data_matrix.dim();
+    let centroid_features = centroids_matrix.ncols();
     
-    // Predict labels
-    let ruby = magnus::Ruby::get().unwrap();
-    let labels_array = RArray::new();
+    if n_features != centroid_features {
+        return Err(dimension_mismatch_error(format!(
+            "Data has {} features but centroids have {}", n_features, centroid_features
+        )));
+    }
     
-    for i in 0..n_samples {
-        let point = data_matrix.row(i);
-        let mut min_dist = f64::INFINITY;
-        let mut best_cluster = 0;
-        
-        for (j, centroid) in centroids_matrix.axis_iter(Axis(0)).enumerate() {
-            let dist = euclidean_distance(&point, &centroid);
-            if dist < min_dist {
-                min_dist = dist;
-                best_cluster = j;
-            }
-        }
-        
-        labels_array.push(Integer::from_value(ruby.eval(&format!("{}", best_cluster)).unwrap()).unwrap())?;
+    if let KMeansMetric::Cosine = metric {
+        normalize_rows(&mut data_matrix);
+        normalize_rows(&mut centroids_matrix);
+    }
+    
+    // Predict labels
+    let labels_array = RArray::with_capacity(n_samples);
+    for (best_cluster, _) in assign_labels(&data_matrix, &centroids_matrix, metric) {
+        labels_array.push(best_cluster)?;
     }
     
     Ok(labels_array)
 }
 
 /// K-means++ initialization
-fn kmeans_plusplus(data: &Array2<f64>, k: usize, random_seed: Option<i64>) -> Result<Array2<f64>, Error> {
+/// With `weights`, each point's chance of being picked is scaled by its weight
+fn kmeans_plusplus(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    metric: KMeansMetric,
+    k: usize,
+    random_seed: Option<i64>,
+) -> Array2<f64> {
     let n_samples = data.nrows();
     let n_features = data.ncols();
     
@@ -169,27 +617,31 @@ fn kmeans_plusplus(data: &Array2<f64>, k: usize, random_seed: Option<i64>) -> Re
     
     let mut centroids = Array2::<f64>::zeros((k, n_features));
     
-    // Choose first centroid randomly
-    let first_idx = rng.gen_range(0..n_samples);
+    let weight = |j: usize| weights.map_or(1.0, |weights| weights[j]);
+    
+    // Choose first centroid with probability proportional to its weight
+    // (uniformly without weights, through the same draw)
+    let first_mass: Vec<f64> = (0..n_samples).map(weight).collect();
+    let first_idx = sample_proportional(&mut rng, &first_mass);
     centroids.row_mut(0).assign(&data.row(first_idx));
     
+    // Squared distance from each point to its nearest chosen centroid
+    let mut distances = vec![f64::INFINITY; n_samples];
+    
     // Choose remaining centroids
     for i in 1..k {
-        let mut distances = vec![f64::INFINITY; n_samples];
-        
-        // Calculate distance to nearest centroid for each point
-        for j in 0..n_samples {
-            for c in 0..i {
-                let dist = euclidean_distance(&data.row(j), &centroids.row(c));
-                if dist < distances[j] {
-                    distances[j] = dist;
-                }
+        // Only the most recently chosen centroid can lower a point's distance
+        let newest = centroids.row(i - 1);
+        distances.par_iter_mut().enumerate().for_each(|(j, min_dist)| {
+            let dist = metric.distance(&data.row(j), &newest);
+            if dist < *min_dist {
+                *min_dist = dist;
             }
-        }
+        });
         
         // Convert distances to probabilities
-        let total: f64 = distances.iter().map(|d| d * d).sum();
-        if total == 0.0 {
+        let mass: Vec<f64> = (0..n_samples).map(|j| distances[j] * weight(j)).collect();
+        if mass.iter().sum::<f64>() == 0.0 {
             // All points are identical or we've selected duplicates
             // Just use sequential points as centroids
             if i < n_samples {
@@ -202,26 +654,127 @@ fn kmeans_plusplus(data: &Array2<f64>, k: usize, random_seed: Option<i64>) -> Re
         }
         
         // Choose next centroid with probability proportional to squared distance
-        let mut cumsum = 0.0;
-        let rand_val: f64 = rng.gen::<f64>() * total;
-        
-        for j in 0..n_samples {
-            cumsum += distances[j] * distances[j];
+        centroids.row_mut(i).assign(&data.row(sample_proportional(&mut rng, &mass)));
+    }
+    
+    centroids
+}
+
+/// Draw an index with probability proportional to its non-negative mass
+/// Rounding that leaves the draw beyond the running total falls back to the
+/// last index with positive mass
+fn sample_proportional<R: Rng + ?Sized>(rng: &mut R, mass: &[f64]) -> usize {
+    let rand_val: f64 = rng.gen::<f64>() * mass.iter().sum::<f64>();
+    let mut cumsum = 0.0;
+    let mut last_positive = 0;
+    for (j, &m) in mass.iter().enumerate() {
+        if m > 0.0 {
+            cumsum += m;
+            last_positive = j;
             if cumsum >= rand_val {
-                centroids.row_mut(i).assign(&data.row(j));
-                break;
+                return j;
             }
         }
     }
+    last_positive
+}
+
+/// Assign each point to its nearest centroid in parallel
+/// Returns (cluster index, distance under `metric`) per point
+fn assign_labels(data: &Array2<f64>, centroids: &Array2<f64>, metric: KMeansMetric) -> Vec<(usize, f64)> {
+    (0..data.nrows())
+        .into_par_iter()
+        .map(|i| nearest_centroid(&data.row(i), centroids, metric))
+        .collect()
+}
+
+/// Find the nearest centroid to a point
+/// Returns (cluster index, distance under `metric`)
+fn nearest_centroid(point: &ArrayView1<f64>, centroids: &Array2<f64>, metric: KMeansMetric) -> (usize, f64) {
+    let mut min_dist = f64::INFINITY;
+    let mut best_cluster = 0;
+    
+    for (j, centroid) in centroids.axis_iter(Axis(0)).enumerate() {
+        let dist = metric.distance(point, &centroid);
+        if dist < min_dist {
+            min_dist = dist;
+            best_cluster = j;
+        }
+    }
     
-    Ok(centroids)
+    (best_cluster, min_dist)
 }
 
-/// Calculate Euclidean distance between two points
-fn euclidean_distance(a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
+/// Sum the (optionally weighted) points assigned to each cluster in parallel
+/// Returns (per-cluster coordinate sums, per-cluster total weights); without
+/// weights the totals are point counts
+fn accumulate_clusters(
+    data: &Array2<f64>,
+    weights: Option<&[f64]>,
+    labels: &[usize],
+    k: usize,
+) -> (Array2<f64>, Vec<f64>) {
+    let n_features = data.ncols();
+    
+    // Fixed chunk boundaries and an in-order merge keep the floating point
+    // summation order, and therefore seeded results, independent of threads
+    let chunk_size = ACCUMULATE_MIN_CHUNK.max(data.nrows().div_ceil(ACCUMULATE_MAX_CHUNKS));
+    let partials: Vec<(Array2<f64>, Vec<f64>)> = labels
+        .par_chunks(chunk_size)
+        .enumerate()
+        .map(|(chunk, chunk_labels)| {
+            let mut sums = Array2::<f64>::zeros((k, n_features));
+            let mut counts = vec![0.0f64; k];
+            for (offset, &label) in chunk_labels.iter().enumerate() {
+                let i = chunk * chunk_size + offset;
+                let mut sum = sums.row_mut(label);
+                match weights {
+                    Some(weights) => {
+                        sum.scaled_add(weights[i], &data.row(i));
+                        counts[label] += weights[i];
+                    }
+                    None => {
+                        sum += &data.row(i);
+                        counts[label] += 1.0;
+                    }
+                }
+            }
+            (sums, counts)
+        })
+        .collect();
+    
+    let mut sums = Array2::<f64>::zeros((k, n_features));
+    let mut counts = vec![0.0f64; k];
+    for (partial_sums, partial_counts) in partials {
+        sums += &partial_sums;
+        for (count, partial) in counts.iter_mut().zip(partial_counts) {
+            *count += partial;
+        }
+    }
+    
+    (sums, counts)
+}
+
+/// Scale each row to unit length; all-zero rows are left unchanged
+fn normalize_rows(matrix: &mut Array2<f64>) {
+    for mut row in matrix.rows_mut() {
+        normalize(&mut row);
+    }
+}
+
+/// Scale a vector to unit length unless it is all zeros
+fn normalize(vector: &mut ArrayViewMut1<f64>) {
+    let norm = vector.dot(vector).sqrt();
+    if norm > 0.0 {
+        *vector /= norm;
+    }
+}
+
+/// Calculate squared Euclidean distance between two points
+/// Avoids the sqrt when only comparisons or inertia are needed
+fn squared_euclidean_distance(a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
     a.iter()
         .zip(b.iter())
         .map(|(x, y)| (x - y).powi(2))
         .sum::<f64>()
-        .sqrt()
-}
\ No newline at end of file
+}
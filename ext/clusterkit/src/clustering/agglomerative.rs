@@ -0,0 +1,227 @@
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use rayon::prelude::*;
+use crate::utils::ruby_array_to_ndarray;
+use super::linkage::{linkage_from_merges, minimum_spanning_tree, LinkageRow};
+use super::metric::Metric;
+
+/// Initialize agglomerative clustering module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "agglomerative_rust",
+        function!(agglomerative, 5),
+    )?;
+
+    Ok(())
+}
+
+/// Distance between two clusters in terms of the distances between their points
+#[derive(Clone, Copy)]
+enum Linkage {
+    /// Closest pair of points
+    Single,
+    /// Farthest pair of points
+    Complete,
+    /// Mean over all pairs of points
+    Average,
+    /// Increase in within-cluster variance; euclidean distances only
+    Ward,
+}
+
+impl Linkage {
+    fn parse(linkage: &str) -> Result<Self, Error> {
+        match linkage {
+            "single" => Ok(Linkage::Single),
+            "complete" => Ok(Linkage::Complete),
+            "average" => Ok(Linkage::Average),
+            "ward" => Ok(Linkage::Ward),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown linkage: {} (expected single, complete, average or ward)", other),
+            )),
+        }
+    }
+
+    /// Lance-Williams update: distance from cluster k to the union of x and y
+    fn merged_distance(self, d_kx: f64, d_ky: f64, d_xy: f64, n_k: usize, n_x: usize, n_y: usize) -> f64 {
+        let (n_k, n_x, n_y) = (n_k as f64, n_x as f64, n_y as f64);
+        match self {
+            Linkage::Single => d_kx.min(d_ky),
+            Linkage::Complete => d_kx.max(d_ky),
+            Linkage::Average => (n_x * d_kx + n_y * d_ky) / (n_x + n_y),
+            Linkage::Ward => {
+                let squared = (n_x + n_k) * d_kx * d_kx + (n_y + n_k) * d_ky * d_ky - n_k * d_xy * d_xy;
+                (squared / (n_x + n_y + n_k)).max(0.0).sqrt()
+            }
+        }
+    }
+}
+
+/// Perform agglomerative (bottom-up hierarchical) clustering
+/// `linkage` is "single", "complete", "average" or "ward" (euclidean only);
+/// `metric` is "euclidean" (or "l2"), "manhattan" (or "l1"), "cosine",
+/// "chebyshev" or "precomputed", in which case `data` is an n x n distance matrix
+/// Exactly one of `n_clusters` and `distance_threshold` cuts the tree: into that
+/// many clusters, or by undoing every merge at or above the threshold
+/// Returns a hash with labels (numbered in order of each cluster's first point),
+/// the number of clusters, and the full merge tree as a SciPy linkage matrix of
+/// [left, right, distance, size] rows, where node n_samples + i is the merge on row i
+pub fn agglomerative(
+    data: Value,
+    n_clusters: Option<usize>,
+    distance_threshold: Option<f64>,
+    linkage: String,
+    metric: String,
+) -> Result<RHash, Error> {
+    let linkage = Linkage::parse(&linkage)?;
+    let metric = Metric::parse(&metric)?;
+
+    if matches!(linkage, Linkage::Ward) && !matches!(metric, Metric::Euclidean) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "Ward linkage requires the euclidean metric",
+        ));
+    }
+
+    if n_clusters.is_some() == distance_threshold.is_some() {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "Exactly one of n_clusters and distance_threshold must be given",
+        ));
+    }
+
+    if let Some(threshold) = distance_threshold {
+        if threshold.is_nan() || threshold < 0.0 {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("distance_threshold must be non-negative (got {})", threshold),
+            ));
+        }
+    }
+
+    let data_array = metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?;
+    let n_samples = data_array.nrows();
+
+    if let Some(k) = n_clusters {
+        if k == 0 || k > n_samples {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("n_clusters ({}) must be between 1 and the number of samples ({})", k, n_samples),
+            ));
+        }
+    }
+
+    let distance = metric.pairwise(&data_array);
+    let merges = match linkage {
+        // Single linkage merges along the minimum spanning tree, without a distance matrix
+        Linkage::Single => minimum_spanning_tree(n_samples, &distance),
+        _ => nn_chain(n_samples, &distance, linkage),
+    };
+    let tree = linkage_from_merges(n_samples, merges);
+
+    let n_merges = match (n_clusters, distance_threshold) {
+        (Some(k), _) => n_samples - k,
+        (None, Some(threshold)) => tree.iter().take_while(|row| row.distance < threshold).count(),
+        (None, None) => unreachable!("checked above"),
+    };
+    let labels = cut_tree(n_samples, &tree[..n_merges]);
+
+    let result = RHash::new();
+    result.aset("labels", RArray::from_vec(labels))?;
+    result.aset("n_clusters", n_samples - n_merges)?;
+    let linkage_matrix = RArray::with_capacity(tree.len());
+    for row in &tree {
+        linkage_matrix.push((row.left, row.right, row.distance, row.size))?;
+    }
+    result.aset("linkage_matrix", linkage_matrix)?;
+    Ok(result)
+}
+
+/// Position of (i, j), i < j, in a condensed upper-triangular distance matrix
+fn condensed_index(n_samples: usize, i: usize, j: usize) -> usize {
+    n_samples * i - i * (i + 1) / 2 + j - i - 1
+}
+
+/// Merges of a reducible linkage by the nearest-neighbor chain algorithm, in
+/// O(n²) time over a condensed distance matrix. Each merge is reported as
+/// (a point of one cluster, a point of the other, distance), not yet in order;
+/// ties go to the previous chain link, then to the lowest index
+fn nn_chain<F>(n_samples: usize, distance: &F, linkage: Linkage) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    let mut distances: Vec<f64> = (0..n_samples)
+        .into_par_iter()
+        .flat_map_iter(|i| (i + 1..n_samples).map(move |j| distance(i, j)))
+        .collect();
+    let d = |distances: &[f64], i: usize, j: usize| distances[condensed_index(n_samples, i.min(j), i.max(j))];
+
+    // A cluster lives on in the slot of one of its points
+    let mut active = vec![true; n_samples];
+    let mut size = vec![1usize; n_samples];
+    let mut chain: Vec<usize> = Vec::with_capacity(n_samples);
+    let mut merges = Vec::with_capacity(n_samples.saturating_sub(1));
+
+    for _ in 1..n_samples {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|&a| a).expect("an active cluster remains"));
+        }
+
+        // Follow nearest neighbors until two clusters are each other's nearest
+        let (x, y) = loop {
+            let x = chain[chain.len() - 1];
+            let previous = chain.len().checked_sub(2).map(|i| chain[i]);
+            let mut nearest = previous;
+            let mut nearest_distance = previous.map_or(f64::INFINITY, |p| d(&distances, x, p));
+            for candidate in (0..n_samples).filter(|&c| active[c] && c != x) {
+                let candidate_distance = d(&distances, x, candidate);
+                if candidate_distance < nearest_distance {
+                    nearest = Some(candidate);
+                    nearest_distance = candidate_distance;
+                }
+            }
+            let y = nearest.expect("another active cluster remains");
+            if Some(y) == previous {
+                break (x, y);
+            }
+            chain.push(y);
+        };
+        chain.truncate(chain.len() - 2);
+
+        let d_xy = d(&distances, x, y);
+        merges.push((x, y, d_xy));
+
+        // The union takes y's slot
+        for k in (0..n_samples).filter(|&k| active[k] && k != x && k != y) {
+            let updated = linkage.merged_distance(d(&distances, k, x), d(&distances, k, y), d_xy, size[k], size[x], size[y]);
+            distances[condensed_index(n_samples, k.min(y), k.max(y))] = updated;
+        }
+        active[x] = false;
+        size[y] += size[x];
+    }
+
+    merges
+}
+
+/// Labels after the given merges, numbered in order of each cluster's first point
+fn cut_tree(n_samples: usize, rows: &[LinkageRow]) -> Vec<i64> {
+    // Every node's members end up under the cluster formed by its last merge
+    let mut owner: Vec<usize> = (0..n_samples + rows.len()).collect();
+    for (row, merge) in rows.iter().enumerate().rev() {
+        let node = owner[n_samples + row];
+        owner[merge.left] = node;
+        owner[merge.right] = node;
+    }
+
+    let mut labels = vec![-1i64; n_samples];
+    let mut label_of_node = vec![-1i64; n_samples + rows.len()];
+    let mut next_label = 0;
+    for point in 0..n_samples {
+        let node = owner[point];
+        if label_of_node[node] == -1 {
+            label_of_node[node] = next_label;
+            next_label += 1;
+        }
+        labels[point] = label_of_node[node];
+    }
+    labels
+}
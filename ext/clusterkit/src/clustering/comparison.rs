@@ -0,0 +1,353 @@
+use magnus::{function, prelude::*, Error, RArray, RHash};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// Initialize external clustering comparison functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "contingency_matrix_rust",
+        function!(contingency_matrix, 3),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "adjusted_rand_score_rust",
+        function!(adjusted_rand_score, 3),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "normalized_mutual_info_score_rust",
+        function!(normalized_mutual_info_score, 3),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "adjusted_mutual_info_score_rust",
+        function!(adjusted_mutual_info_score, 3),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "homogeneity_completeness_v_measure_rust",
+        function!(homogeneity_completeness_v_measure, 4),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "fowlkes_mallows_score_rust",
+        function!(fowlkes_mallows_score, 3),
+    )?;
+
+    Ok(())
+}
+
+/// How points labeled as noise (negative labels) enter a comparison
+#[derive(Clone, Copy)]
+enum NoisePolicy {
+    /// Drop points that are noise in either labeling
+    Exclude,
+    /// Treat all noise points as one more cluster
+    Cluster,
+    /// Treat every noise point as a cluster of its own
+    Singletons,
+}
+
+impl NoisePolicy {
+    fn parse(noise: &str) -> Result<Self, Error> {
+        match noise {
+            "exclude" => Ok(NoisePolicy::Exclude),
+            "cluster" => Ok(NoisePolicy::Cluster),
+            "singletons" => Ok(NoisePolicy::Singletons),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown noise policy: {} (expected exclude, cluster or singletons)", other),
+            )),
+        }
+    }
+
+    /// Key identifying the cluster of point `i`; noise singletons get a key of their own
+    fn key(self, label: i64, i: usize) -> (i64, usize) {
+        match self {
+            NoisePolicy::Singletons if label < 0 => (label, i + 1),
+            _ => (label, 0),
+        }
+    }
+}
+
+/// Sparse contingency table between a reference labeling (rows) and a
+/// predicted labeling (columns)
+struct Contingency {
+    n_samples: usize,
+    // Non-zero cells as (row, column, count)
+    cells: Vec<(usize, usize, usize)>,
+    row_sums: Vec<usize>,
+    col_sums: Vec<usize>,
+    row_labels: Vec<i64>,
+    col_labels: Vec<i64>,
+}
+
+impl Contingency {
+    fn new(labels_true: &[i64], labels_pred: &[i64], noise: &str) -> Result<Self, Error> {
+        if labels_true.len() != labels_pred.len() {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!(
+                    "labels_true has {} entries but labels_pred has {}",
+                    labels_true.len(), labels_pred.len()
+                ),
+            ));
+        }
+        let noise = NoisePolicy::parse(noise)?;
+
+        let kept: Vec<usize> = (0..labels_true.len())
+            .filter(|&i| match noise {
+                NoisePolicy::Exclude => labels_true[i] >= 0 && labels_pred[i] >= 0,
+                _ => true,
+            })
+            .collect();
+
+        // Rows and columns are ordered by label, noise singletons in point order
+        let mut rows = BTreeMap::new();
+        let mut cols = BTreeMap::new();
+        for &i in &kept {
+            rows.insert(noise.key(labels_true[i], i), 0);
+            cols.insert(noise.key(labels_pred[i], i), 0);
+        }
+        for (index, value) in rows.values_mut().enumerate() {
+            *value = index;
+        }
+        for (index, value) in cols.values_mut().enumerate() {
+            *value = index;
+        }
+
+        let mut counts = BTreeMap::new();
+        let mut row_sums = vec![0usize; rows.len()];
+        let mut col_sums = vec![0usize; cols.len()];
+        for &i in &kept {
+            let row = rows[&noise.key(labels_true[i], i)];
+            let col = cols[&noise.key(labels_pred[i], i)];
+            *counts.entry((row, col)).or_insert(0usize) += 1;
+            row_sums[row] += 1;
+            col_sums[col] += 1;
+        }
+
+        Ok(Contingency {
+            n_samples: kept.len(),
+            cells: counts.into_iter().map(|((row, col), count)| (row, col, count)).collect(),
+            row_sums,
+            col_sums,
+            row_labels: rows.keys().map(|&(label, _)| label).collect(),
+            col_labels: cols.keys().map(|&(label, _)| label).collect(),
+        })
+    }
+
+    /// Both labelings put everything in one cluster (or there is nothing to
+    /// compare), in which case they trivially agree
+    fn trivially_identical(&self) -> bool {
+        (self.row_sums.len() == self.col_sums.len()) && self.row_sums.len() <= 1
+    }
+
+    fn mutual_info(&self) -> f64 {
+        let n = self.n_samples as f64;
+        self.cells
+            .iter()
+            .map(|&(row, col, count)| {
+                let count = count as f64;
+                let expected = self.row_sums[row] as f64 * self.col_sums[col] as f64;
+                count / n * (n * count / expected).ln()
+            })
+            .sum::<f64>()
+            .max(0.0)
+    }
+}
+
+/// Shannon entropy (natural log) of a labeling given its cluster sizes
+fn entropy(sizes: &[usize]) -> f64 {
+    let n: usize = sizes.iter().sum();
+    if n == 0 {
+        return 0.0;
+    }
+    let n = n as f64;
+    sizes
+        .iter()
+        .filter(|&&size| size > 0)
+        .map(|&size| {
+            let p = size as f64 / n;
+            -p * p.ln()
+        })
+        .sum()
+}
+
+/// Number of unordered pairs among `n` items
+fn pairs(n: usize) -> f64 {
+    let n = n as f64;
+    n * (n - 1.0) / 2.0
+}
+
+/// Contingency matrix as a hash with the dense matrix and its row
+/// (labels_true) and column (labels_pred) labels
+fn contingency_matrix(labels_true: Vec<i64>, labels_pred: Vec<i64>, noise: String) -> Result<RHash, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+
+    let mut dense = vec![vec![0usize; table.col_sums.len()]; table.row_sums.len()];
+    for &(row, col, count) in &table.cells {
+        dense[row][col] = count;
+    }
+
+    let matrix = RArray::with_capacity(dense.len());
+    for row in dense {
+        matrix.push(RArray::from_vec(row))?;
+    }
+
+    let result = RHash::new();
+    result.aset("matrix", matrix)?;
+    result.aset("true_labels", RArray::from_vec(table.row_labels))?;
+    result.aset("pred_labels", RArray::from_vec(table.col_labels))?;
+    Ok(result)
+}
+
+/// Rand index adjusted for chance: 1 for identical partitions, about 0 for
+/// random ones, and possibly negative
+fn adjusted_rand_score(labels_true: Vec<i64>, labels_pred: Vec<i64>, noise: String) -> Result<f64, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+    if table.trivially_identical() {
+        return Ok(1.0);
+    }
+
+    let index: f64 = table.cells.iter().map(|&(_, _, count)| pairs(count)).sum();
+    let row_pairs: f64 = table.row_sums.iter().map(|&size| pairs(size)).sum();
+    let col_pairs: f64 = table.col_sums.iter().map(|&size| pairs(size)).sum();
+    let expected = row_pairs * col_pairs / pairs(table.n_samples);
+    let max_index = (row_pairs + col_pairs) / 2.0;
+
+    if max_index == expected {
+        // Both labelings are all singletons or all one cluster
+        return Ok(1.0);
+    }
+    Ok((index - expected) / (max_index - expected))
+}
+
+/// Mutual information normalized by the arithmetic mean of the entropies
+fn normalized_mutual_info_score(labels_true: Vec<i64>, labels_pred: Vec<i64>, noise: String) -> Result<f64, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+    if table.trivially_identical() {
+        return Ok(1.0);
+    }
+
+    let normalizer = (entropy(&table.row_sums) + entropy(&table.col_sums)) / 2.0;
+    if normalizer == 0.0 {
+        return Ok(0.0);
+    }
+    Ok((table.mutual_info() / normalizer).min(1.0))
+}
+
+/// Mutual information adjusted for chance, normalized by the arithmetic mean
+/// of the entropies
+fn adjusted_mutual_info_score(labels_true: Vec<i64>, labels_pred: Vec<i64>, noise: String) -> Result<f64, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+    if table.trivially_identical() {
+        return Ok(1.0);
+    }
+
+    let mutual_info = table.mutual_info();
+    let expected = expected_mutual_info(&table.row_sums, &table.col_sums, table.n_samples);
+    let normalizer = (entropy(&table.row_sums) + entropy(&table.col_sums)) / 2.0;
+
+    let mut denominator = normalizer - expected;
+    if denominator < 0.0 {
+        denominator = denominator.min(-f64::EPSILON);
+    } else {
+        denominator = denominator.max(f64::EPSILON);
+    }
+    Ok((mutual_info - expected) / denominator)
+}
+
+/// Expected mutual information between two random labelings with the given
+/// cluster sizes, under the hypergeometric model of Vinh et al. (2010)
+fn expected_mutual_info(row_sums: &[usize], col_sums: &[usize], n_samples: usize) -> f64 {
+    let n = n_samples as f64;
+
+    // ln(k!) for every k up to n
+    let mut ln_factorial = vec![0.0f64; n_samples + 1];
+    for k in 1..=n_samples {
+        ln_factorial[k] = ln_factorial[k - 1] + (k as f64).ln();
+    }
+
+    let terms: Vec<f64> = row_sums
+        .par_iter()
+        .map(|&a| {
+            let mut total = 0.0;
+            for &b in col_sums {
+                let start = (a + b).saturating_sub(n_samples).max(1);
+                let end = a.min(b);
+                for nij in start..=end {
+                    let nij_f = nij as f64;
+                    let term = nij_f / n * (n * nij_f / (a as f64 * b as f64)).ln();
+                    let ln_probability = ln_factorial[a] + ln_factorial[b]
+                        + ln_factorial[n_samples - a] + ln_factorial[n_samples - b]
+                        - ln_factorial[n_samples] - ln_factorial[nij]
+                        - ln_factorial[a - nij] - ln_factorial[b - nij]
+                        - ln_factorial[n_samples + nij - a - b];
+                    total += term * ln_probability.exp();
+                }
+            }
+            total
+        })
+        .collect();
+
+    terms.iter().sum()
+}
+
+/// Homogeneity (each cluster holds one class), completeness (each class is in
+/// one cluster) and their weighted harmonic mean, the V-measure
+/// `beta` > 1 weights completeness more, < 1 weights homogeneity more
+fn homogeneity_completeness_v_measure(
+    labels_true: Vec<i64>,
+    labels_pred: Vec<i64>,
+    noise: String,
+    beta: f64,
+) -> Result<RArray, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+    if beta.is_nan() || beta < 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("beta must be non-negative (got {})", beta),
+        ));
+    }
+
+    let (homogeneity, completeness) = if table.n_samples == 0 {
+        (1.0, 1.0)
+    } else {
+        let mutual_info = table.mutual_info();
+        let entropy_true = entropy(&table.row_sums);
+        let entropy_pred = entropy(&table.col_sums);
+        (
+            if entropy_true > 0.0 { (mutual_info / entropy_true).min(1.0) } else { 1.0 },
+            if entropy_pred > 0.0 { (mutual_info / entropy_pred).min(1.0) } else { 1.0 },
+        )
+    };
+
+    // With beta = 0 the denominator vanishes whenever completeness does
+    let denominator = beta * homogeneity + completeness;
+    let v_measure = if denominator > 0.0 {
+        (1.0 + beta) * homogeneity * completeness / denominator
+    } else {
+        0.0
+    };
+
+    Ok(RArray::from_vec(vec![homogeneity, completeness, v_measure]))
+}
+
+/// Geometric mean of pairwise precision and recall
+fn fowlkes_mallows_score(labels_true: Vec<i64>, labels_pred: Vec<i64>, noise: String) -> Result<f64, Error> {
+    let table = Contingency::new(&labels_true, &labels_pred, &noise)?;
+    if table.trivially_identical() {
+        return Ok(1.0);
+    }
+
+    // Pairs together in both, in the reference, and in the prediction
+    let together: f64 = table.cells.iter().map(|&(_, _, count)| pairs(count)).sum();
+    let together_true: f64 = table.row_sums.iter().map(|&size| pairs(size)).sum();
+    let together_pred: f64 = table.col_sums.iter().map(|&size| pairs(size)).sum();
+
+    if together == 0.0 {
+        return Ok(0.0);
+    }
+    Ok((together / together_true).sqrt() * (together / together_pred).sqrt())
+}
@@ -0,0 +1,88 @@
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use rayon::prelude::*;
+use crate::utils::ruby_array_to_ndarray;
+use super::metric::Metric;
+use super::neighbors::NeighborIndex;
+
+/// Initialize DBSCAN module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "dbscan_rust",
+        function!(dbscan, 4),
+    )?;
+
+    Ok(())
+}
+
+/// Perform DBSCAN clustering
+/// Points with at least `min_samples` neighbors (themselves included) within
+/// `eps` are core points; clusters are the connected core points plus the
+/// border points they reach. `metric` is "euclidean" (or "l2"), "manhattan"
+/// (or "l1"), "cosine", "chebyshev" or "precomputed", in which case `data` is
+/// an n x n distance matrix
+/// Returns a hash with labels (-1 for noise) and core sample indices
+pub fn dbscan(data: Value, eps: f64, min_samples: usize, metric: String) -> Result<RHash, Error> {
+    let metric = Metric::parse(&metric)?;
+
+    if !(eps > 0.0 && eps.is_finite()) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("eps must be positive (got {})", eps),
+        ));
+    }
+
+    if min_samples == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "min_samples must be positive",
+        ));
+    }
+
+    let data_array = metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?;
+    let index = NeighborIndex::new(&data_array, metric);
+
+    let neighborhoods: Vec<Vec<usize>> = (0..data_array.nrows())
+        .into_par_iter()
+        .map(|point| index.radius_neighbors(point, eps).into_iter().map(|(other, _)| other).collect())
+        .collect();
+    let labels = dbscan_labels(&neighborhoods, min_samples);
+    let core_sample_indices: Vec<usize> = (0..neighborhoods.len())
+        .filter(|&point| neighborhoods[point].len() >= min_samples)
+        .collect();
+
+    let result = RHash::new();
+    result.aset("labels", RArray::from_vec(labels))?;
+    result.aset("core_sample_indices", RArray::from_vec(core_sample_indices))?;
+    Ok(result)
+}
+
+/// Grow clusters from core points in index order; a border point joins the
+/// first cluster that reaches it
+fn dbscan_labels(neighborhoods: &[Vec<usize>], min_samples: usize) -> Vec<i64> {
+    let is_core: Vec<bool> = neighborhoods.iter().map(|neighbors| neighbors.len() >= min_samples).collect();
+    let mut labels = vec![-1i64; neighborhoods.len()];
+    let mut label = 0;
+    let mut stack = Vec::new();
+
+    for seed in 0..neighborhoods.len() {
+        if labels[seed] != -1 || !is_core[seed] {
+            continue;
+        }
+        labels[seed] = label;
+        stack.push(seed);
+        while let Some(point) = stack.pop() {
+            if !is_core[point] {
+                continue;
+            }
+            for &neighbor in &neighborhoods[point] {
+                if labels[neighbor] == -1 {
+                    labels[neighbor] = label;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        label += 1;
+    }
+
+    labels
+}
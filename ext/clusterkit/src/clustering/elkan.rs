@@ -0,0 +1,156 @@
+use ndarray::Array2;
+use rayon::prelude::*;
+use super::squared_euclidean_distance;
+
+// Relative slack on bound comparisons so floating point drift in the bounds
+// can never skip a distance that Lloyd's full pass would have compared
+const BOUND_SLACK: f64 = 1e-9;
+
+/// Triangle-inequality bounds for Elkan's accelerated k-means
+///
+/// Keeps an upper bound on each point's distance to its assigned centroid and
+/// a lower bound on its distance to every centroid. A centroid is only
+/// compared against a point when the bounds cannot rule it out, so each
+/// assignment step matches Lloyd's full pass exactly (including the
+/// lowest-index tie break) while skipping most distance computations.
+pub(super) struct ElkanBounds {
+    k: usize,
+    upper: Vec<f64>,
+    // Row-major n_samples x k lower bounds
+    lower: Vec<f64>,
+}
+
+impl ElkanBounds {
+    /// Compute every point-centroid distance once to seed the bounds
+    /// Returns the bounds and the initial labels
+    pub(super) fn new(data: &Array2<f64>, centroids: &Array2<f64>) -> (Self, Vec<usize>) {
+        let k = centroids.nrows();
+        let n_samples = data.nrows();
+        let mut upper = vec![0.0; n_samples];
+        let mut lower = vec![0.0; n_samples * k];
+        let mut labels = vec![0usize; n_samples];
+
+        labels
+            .par_iter_mut()
+            .zip(upper.par_iter_mut())
+            .zip(lower.par_chunks_mut(k))
+            .enumerate()
+            .for_each(|(i, ((label, upper), lower))| {
+                let point = data.row(i);
+                let mut best_dist = f64::INFINITY;
+                for (j, centroid) in centroids.rows().into_iter().enumerate() {
+                    let dist = squared_euclidean_distance(&point, &centroid);
+                    lower[j] = dist.sqrt();
+                    if dist < best_dist {
+                        best_dist = dist;
+                        *label = j;
+                    }
+                }
+                *upper = best_dist.sqrt();
+            });
+
+        (ElkanBounds { k, upper, lower }, labels)
+    }
+
+    /// Loosen the bounds after each centroid moved by `shifts[j]`
+    pub(super) fn update(&mut self, labels: &[usize], shifts: &[f64]) {
+        let k = self.k;
+        self.upper
+            .par_iter_mut()
+            .zip(self.lower.par_chunks_mut(k))
+            .zip(labels.par_iter())
+            .for_each(|((upper, lower), &label)| {
+                *upper += shifts[label];
+                for (bound, shift) in lower.iter_mut().zip(shifts) {
+                    *bound = (*bound - shift).max(0.0);
+                }
+            });
+    }
+
+    /// Reassign points to their nearest centroid, skipping centroids the bounds rule out
+    /// Returns true if any label changed
+    pub(super) fn assign(&mut self, data: &Array2<f64>, centroids: &Array2<f64>, labels: &mut [usize]) -> bool {
+        let k = self.k;
+
+        // Half the distance from each centroid to every other, and to its nearest other
+        let mut half_centroid_dist = Array2::<f64>::zeros((k, k));
+        for a in 0..k {
+            for b in (a + 1)..k {
+                let dist = 0.5 * squared_euclidean_distance(&centroids.row(a), &centroids.row(b)).sqrt();
+                half_centroid_dist[[a, b]] = dist;
+                half_centroid_dist[[b, a]] = dist;
+            }
+        }
+        let half_nearest: Vec<f64> = (0..k)
+            .map(|a| {
+                (0..k)
+                    .filter(|&b| b != a)
+                    .map(|b| half_centroid_dist[[a, b]])
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect();
+
+        let changed = labels
+            .par_iter_mut()
+            .zip(self.upper.par_iter_mut())
+            .zip(self.lower.par_chunks_mut(k))
+            .enumerate()
+            .map(|(i, ((label, upper), lower))| {
+                let original = *label;
+                let mut best = original;
+
+                // No other centroid can be closer than half the gap to the nearest one
+                if loosened(*upper) < half_nearest[best] {
+                    return false;
+                }
+
+                let point = data.row(i);
+                let mut best_dist: Option<f64> = None;
+
+                for j in 0..k {
+                    if j == best || skip(*upper, lower[j], half_centroid_dist[[best, j]]) {
+                        continue;
+                    }
+
+                    // Tighten the upper bound once before comparing
+                    let current = match best_dist {
+                        Some(dist) => dist,
+                        None => {
+                            let dist = squared_euclidean_distance(&point, &centroids.row(best));
+                            *upper = dist.sqrt();
+                            lower[best] = *upper;
+                            best_dist = Some(dist);
+                            if skip(*upper, lower[j], half_centroid_dist[[best, j]]) {
+                                continue;
+                            }
+                            dist
+                        }
+                    };
+
+                    let dist = squared_euclidean_distance(&point, &centroids.row(j));
+                    lower[j] = dist.sqrt();
+                    if dist < current || (dist == current && j < best) {
+                        best = j;
+                        best_dist = Some(dist);
+                        *upper = lower[j];
+                    }
+                }
+
+                *label = best;
+                best != original
+            })
+            .reduce(|| false, |a, b| a || b);
+
+        changed
+    }
+}
+
+/// Centroid j cannot beat the current one for this point
+fn skip(upper: f64, lower: f64, half_centroid_dist: f64) -> bool {
+    let upper = loosened(upper);
+    upper < lower || upper < half_centroid_dist
+}
+
+fn loosened(upper: f64) -> f64 {
+    upper * (1.0 + BOUND_SLACK)
+}
@@ -0,0 +1,466 @@
+use magnus::{class, function, method, prelude::*, Error, Value, RArray, RHash};
+use ndarray::{Array1, Array2, Axis};
+use rayon::prelude::*;
+use crate::errors::{convergence_error, dimension_mismatch_error};
+use crate::utils::ruby_array_to_ndarray;
+use super::{assign_labels, kmeans_plusplus, KMeansMetric};
+
+/// ln(2π)
+const LOG_2PI: f64 = 1.837_877_066_409_345_5;
+
+/// Initialize Gaussian mixture module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "gmm_rust",
+        function!(gmm_fit, 8),
+    )?;
+
+    let model_class = clustering_module.define_class("GaussianMixtureModel", class::object())?;
+    model_class.define_method("predict", method!(GaussianMixtureModel::predict, 1))?;
+    model_class.define_method("predict_proba", method!(GaussianMixtureModel::predict_proba, 1))?;
+    model_class.define_method("score_samples", method!(GaussianMixtureModel::score_samples, 1))?;
+    model_class.define_method("bic", method!(GaussianMixtureModel::bic, 1))?;
+    model_class.define_method("aic", method!(GaussianMixtureModel::aic, 1))?;
+
+    Ok(())
+}
+
+/// Shape of each component's covariance
+#[derive(Clone, Copy)]
+enum CovarianceType {
+    /// A full d x d matrix per component
+    Full,
+    /// Independent variances per feature
+    Diag,
+    /// A single variance per component
+    Spherical,
+}
+
+impl CovarianceType {
+    fn parse(covariance_type: &str) -> Result<Self, Error> {
+        match covariance_type {
+            "full" => Ok(CovarianceType::Full),
+            "diag" => Ok(CovarianceType::Diag),
+            "spherical" => Ok(CovarianceType::Spherical),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown covariance type: {} (expected full, diag or spherical)", other),
+            )),
+        }
+    }
+}
+
+/// Component covariances, stored according to their type
+#[derive(Clone)]
+enum Covariances {
+    Full(Vec<Array2<f64>>),
+    /// k x d variances
+    Diag(Array2<f64>),
+    Spherical(Vec<f64>),
+}
+
+/// Mixture weights, means and covariances
+#[derive(Clone)]
+struct MixtureParams {
+    weights: Vec<f64>,
+    means: Array2<f64>,
+    covariances: Covariances,
+}
+
+/// Settings shared by every EM run
+struct MixtureSettings {
+    n_components: usize,
+    covariance_type: CovarianceType,
+    tol: f64,
+    reg_covar: f64,
+    max_iter: usize,
+}
+
+/// Result of a single EM run
+struct MixtureRun {
+    params: MixtureParams,
+    lower_bound: f64,
+    n_iter: usize,
+    converged: bool,
+}
+
+/// Per-component factor of the Gaussian log density
+enum Precision {
+    /// Lower Cholesky factor of the covariance
+    Cholesky(Array2<f64>),
+    Variances(Array1<f64>),
+}
+
+impl MixtureParams {
+    /// Free parameters of the model, as counted by BIC and AIC
+    fn n_parameters(&self) -> usize {
+        let (k, d) = self.means.dim();
+        let covariance_parameters = match self.covariances {
+            Covariances::Full(_) => k * d * (d + 1) / 2,
+            Covariances::Diag(_) => k * d,
+            Covariances::Spherical(_) => k,
+        };
+        covariance_parameters + k * d + k - 1
+    }
+
+    /// log(weight_k) + log N(x_i | mean_k, covariance_k) for every point and
+    /// component; None when a covariance is not positive definite
+    fn weighted_log_prob(&self, data: &Array2<f64>) -> Option<Array2<f64>> {
+        let (n_components, n_features) = self.means.dim();
+        let precisions: Vec<(Precision, f64)> = (0..n_components)
+            .map(|c| match &self.covariances {
+                Covariances::Full(covariances) => {
+                    let factor = cholesky(&covariances[c])?;
+                    let log_det = 2.0 * factor.diag().iter().map(|x| x.ln()).sum::<f64>();
+                    Some((Precision::Cholesky(factor), log_det))
+                }
+                Covariances::Diag(variances) => {
+                    let variances = variances.row(c).to_owned();
+                    if variances.iter().any(|&v| v.is_nan() || v <= 0.0) {
+                        return None;
+                    }
+                    let log_det = variances.iter().map(|v| v.ln()).sum();
+                    Some((Precision::Variances(variances), log_det))
+                }
+                Covariances::Spherical(variances) => {
+                    let variance = variances[c];
+                    if variance.is_nan() || variance <= 0.0 {
+                        return None;
+                    }
+                    let log_det = n_features as f64 * variance.ln();
+                    Some((Precision::Variances(Array1::from_elem(n_features, variance)), log_det))
+                }
+            })
+            .collect::<Option<_>>()?;
+
+        let rows: Vec<f64> = (0..data.nrows())
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let precisions = &precisions;
+                (0..n_components).map(move |c| {
+                    let diff = &data.row(i) - &self.means.row(c);
+                    let (precision, log_det) = &precisions[c];
+                    let mahalanobis = match precision {
+                        Precision::Cholesky(factor) => forward_substitution(factor, &diff).iter().map(|z| z * z).sum(),
+                        Precision::Variances(variances) => diff.iter().zip(variances).map(|(x, v)| x * x / v).sum::<f64>(),
+                    };
+                    self.weights[c].ln() - 0.5 * (n_features as f64 * LOG_2PI + log_det + mahalanobis)
+                })
+            })
+            .collect();
+        Some(Array2::from_shape_vec((data.nrows(), n_components), rows).expect("one entry per point and component"))
+    }
+
+    /// Mean log-likelihood of the data and each point's log responsibilities
+    fn e_step(&self, data: &Array2<f64>) -> Option<(f64, Array2<f64>)> {
+        let mut log_resp = self.weighted_log_prob(data)?;
+        let log_prob_norm = log_sum_exp_rows(&log_resp);
+        for (mut row, norm) in log_resp.axis_iter_mut(Axis(0)).zip(&log_prob_norm) {
+            row -= *norm;
+        }
+        Some((log_prob_norm.mean().unwrap_or(0.0), log_resp))
+    }
+}
+
+/// log Σ_k exp(x_ik) for every row, stable for large magnitudes
+fn log_sum_exp_rows(values: &Array2<f64>) -> Array1<f64> {
+    values.map_axis(Axis(1), |row| {
+        let max = row.fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+        if max.is_infinite() {
+            return max;
+        }
+        max + row.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+    })
+}
+
+/// Lower-triangular L with L Lᵀ = matrix; None unless matrix is positive definite
+fn cholesky(matrix: &Array2<f64>) -> Option<Array2<f64>> {
+    let n = matrix.nrows();
+    let mut factor = Array2::<f64>::zeros((n, n));
+    for j in 0..n {
+        let diagonal = matrix[[j, j]] - (0..j).map(|p| factor[[j, p]] * factor[[j, p]]).sum::<f64>();
+        if diagonal.is_nan() || diagonal <= 0.0 {
+            return None;
+        }
+        let pivot = diagonal.sqrt();
+        factor[[j, j]] = pivot;
+        for i in j + 1..n {
+            let value = matrix[[i, j]] - (0..j).map(|p| factor[[i, p]] * factor[[j, p]]).sum::<f64>();
+            factor[[i, j]] = value / pivot;
+        }
+    }
+    Some(factor)
+}
+
+/// Solve L z = b for lower-triangular L
+fn forward_substitution(factor: &Array2<f64>, b: &Array1<f64>) -> Array1<f64> {
+    let mut z = Array1::<f64>::zeros(b.len());
+    for i in 0..b.len() {
+        let partial = (0..i).map(|p| factor[[i, p]] * z[p]).sum::<f64>();
+        z[i] = (b[i] - partial) / factor[[i, i]];
+    }
+    z
+}
+
+/// Weights, means and covariances maximizing the expected log-likelihood
+/// under responsibilities `resp` (n x k); `reg_covar` is added to every variance
+fn m_step(data: &Array2<f64>, resp: &Array2<f64>, settings: &MixtureSettings) -> MixtureParams {
+    let n_samples = data.nrows() as f64;
+    // A tiny floor keeps empty components from dividing by zero
+    let nk = resp.sum_axis(Axis(0)) + 10.0 * f64::EPSILON;
+    let means = resp.t().dot(data) / nk.view().insert_axis(Axis(1));
+
+    let spread = |c: usize| -> Array2<f64> {
+        let diff = data - &means.row(c);
+        let weighted = &diff * &resp.column(c).insert_axis(Axis(1));
+        weighted.t().dot(&diff) / nk[c]
+    };
+    let variances = |c: usize| -> Array1<f64> {
+        let diff = data - &means.row(c);
+        (&diff * &diff).t().dot(&resp.column(c)) / nk[c] + settings.reg_covar
+    };
+
+    let n_components = settings.n_components;
+    let covariances = match settings.covariance_type {
+        CovarianceType::Full => Covariances::Full(
+            (0..n_components)
+                .into_par_iter()
+                .map(|c| {
+                    let mut covariance = spread(c);
+                    covariance.diag_mut().mapv_inplace(|v| v + settings.reg_covar);
+                    covariance
+                })
+                .collect(),
+        ),
+        CovarianceType::Diag => {
+            let rows: Vec<Array1<f64>> = (0..n_components).into_par_iter().map(variances).collect();
+            let mut diag = Array2::<f64>::zeros((n_components, data.ncols()));
+            for (mut row, values) in diag.axis_iter_mut(Axis(0)).zip(rows) {
+                row.assign(&values);
+            }
+            Covariances::Diag(diag)
+        }
+        CovarianceType::Spherical => Covariances::Spherical(
+            (0..n_components)
+                .into_par_iter()
+                .map(|c| variances(c).mean().unwrap_or(settings.reg_covar))
+                .collect(),
+        ),
+    };
+
+    MixtureParams {
+        weights: nk.iter().map(|n| n / n_samples).collect(),
+        means,
+        covariances,
+    }
+}
+
+/// One EM run, started from each point's nearest k-means++ seed; None when a
+/// covariance stops being positive definite
+fn fit_single(data: &Array2<f64>, settings: &MixtureSettings, random_seed: Option<i64>) -> Option<MixtureRun> {
+    let seeds = kmeans_plusplus(data, None, KMeansMetric::Euclidean, settings.n_components, random_seed);
+    let mut resp = Array2::<f64>::zeros((data.nrows(), settings.n_components));
+    for (point, (label, _)) in assign_labels(data, &seeds, KMeansMetric::Euclidean).into_iter().enumerate() {
+        resp[[point, label]] = 1.0;
+    }
+    let mut params = m_step(data, &resp, settings);
+
+    let mut lower_bound = f64::NEG_INFINITY;
+    let mut n_iter = 0;
+    let mut converged = false;
+    for iteration in 1..=settings.max_iter {
+        n_iter = iteration;
+        let previous = lower_bound;
+        let (log_prob_norm, log_resp) = params.e_step(data)?;
+        params = m_step(data, &log_resp.mapv(f64::exp), settings);
+        lower_bound = log_prob_norm;
+
+        if (lower_bound - previous).abs() < settings.tol {
+            converged = true;
+            break;
+        }
+    }
+
+    Some(MixtureRun { params, lower_bound, n_iter, converged })
+}
+
+/// Index of the largest value in each row; ties go to the lowest index
+fn argmax_rows(values: &Array2<f64>) -> Vec<usize> {
+    values
+        .axis_iter(Axis(0))
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .fold((0, f64::NEG_INFINITY), |best, (c, &v)| if v > best.1 { (c, v) } else { best })
+                .0
+        })
+        .collect()
+}
+
+fn ill_defined_covariance() -> Error {
+    convergence_error(
+        "Fitting the mixture model failed because some components have ill-defined empirical covariance \
+         (for instance caused by singleton or collapsed samples). Try to decrease the number of components, \
+         or increase reg_covar.",
+    )
+}
+
+fn matrix_to_ruby(matrix: &Array2<f64>) -> Result<RArray, Error> {
+    let rows = RArray::with_capacity(matrix.nrows());
+    for row in matrix.rows() {
+        rows.push(RArray::from_vec(row.to_vec()))?;
+    }
+    Ok(rows)
+}
+
+/// Fitted mixture kept on the Ruby side to score new points
+#[magnus::wrap(class = "ClusterKit::Clustering::GaussianMixtureModel", free_immediately, size)]
+struct GaussianMixtureModel {
+    params: MixtureParams,
+}
+
+impl GaussianMixtureModel {
+    fn weighted_log_prob(&self, data: Value) -> Result<Array2<f64>, Error> {
+        let data_array = ruby_array_to_ndarray::<f64>(data)?;
+        let expected = self.params.means.ncols();
+        if data_array.ncols() != expected {
+            return Err(dimension_mismatch_error(format!(
+                "Data has {} features but the model was fitted on {}",
+                data_array.ncols(), expected
+            )));
+        }
+        self.params.weighted_log_prob(&data_array).ok_or_else(ill_defined_covariance)
+    }
+
+    /// Most likely component of each point
+    fn predict(&self, data: Value) -> Result<RArray, Error> {
+        let log_prob = self.weighted_log_prob(data)?;
+        Ok(RArray::from_vec(argmax_rows(&log_prob)))
+    }
+
+    /// Posterior probability of each component for each point
+    fn predict_proba(&self, data: Value) -> Result<RArray, Error> {
+        let mut log_prob = self.weighted_log_prob(data)?;
+        let norm = log_sum_exp_rows(&log_prob);
+        for (mut row, norm) in log_prob.axis_iter_mut(Axis(0)).zip(&norm) {
+            row.mapv_inplace(|v| (v - norm).exp());
+        }
+        matrix_to_ruby(&log_prob)
+    }
+
+    /// Log-likelihood of each point under the mixture
+    fn score_samples(&self, data: Value) -> Result<RArray, Error> {
+        let log_prob = self.weighted_log_prob(data)?;
+        Ok(RArray::from_vec(log_sum_exp_rows(&log_prob).to_vec()))
+    }
+
+    /// Total log-likelihood of the data and its number of points
+    fn log_likelihood(&self, data: Value) -> Result<(f64, f64), Error> {
+        let log_prob = self.weighted_log_prob(data)?;
+        Ok((log_sum_exp_rows(&log_prob).sum(), log_prob.nrows() as f64))
+    }
+
+    /// Bayesian information criterion on the data; lower is better
+    fn bic(&self, data: Value) -> Result<f64, Error> {
+        let (log_likelihood, n_samples) = self.log_likelihood(data)?;
+        Ok(-2.0 * log_likelihood + self.params.n_parameters() as f64 * n_samples.ln())
+    }
+
+    /// Akaike information criterion on the data; lower is better
+    fn aic(&self, data: Value) -> Result<f64, Error> {
+        let (log_likelihood, _) = self.log_likelihood(data)?;
+        Ok(-2.0 * log_likelihood + 2.0 * self.params.n_parameters() as f64)
+    }
+}
+
+/// Fit a Gaussian mixture model by expectation-maximization
+/// `covariance_type` is "full", "diag" or "spherical"; `reg_covar` is added to
+/// every variance to keep covariances positive definite. EM stops once the mean
+/// log-likelihood improves by less than `tol`, or after `max_iter` iterations
+/// Runs `n_init` k-means++ initializations in parallel and keeps the highest
+/// likelihood
+/// Returns a hash with labels, weights, means, covariances (per component: a
+/// d x d matrix, d variances or one variance, by type), converged, n_iter, the
+/// final lower bound (mean log-likelihood) and the fitted model for scoring
+#[allow(clippy::too_many_arguments)]
+pub fn gmm_fit(
+    data: Value,
+    n_components: usize,
+    covariance_type: String,
+    tol: f64,
+    reg_covar: f64,
+    max_iter: usize,
+    n_init: usize,
+    random_seed: Option<i64>,
+) -> Result<RHash, Error> {
+    let covariance_type = CovarianceType::parse(&covariance_type)?;
+    let data_array = ruby_array_to_ndarray::<f64>(data)?;
+    let n_samples = data_array.nrows();
+
+    if n_components == 0 || n_components > n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("n_components ({}) must be between 1 and the number of samples ({})", n_components, n_samples),
+        ));
+    }
+
+    if tol.is_nan() || tol < 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("tol must be non-negative (got {})", tol),
+        ));
+    }
+
+    if reg_covar.is_nan() || reg_covar < 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("reg_covar must be non-negative (got {})", reg_covar),
+        ));
+    }
+
+    if max_iter == 0 || n_init == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "max_iter and n_init must be at least 1",
+        ));
+    }
+
+    let settings = MixtureSettings { n_components, covariance_type, tol, reg_covar, max_iter };
+
+    // Seeds derived as in k-means, so the first run matches a single-initialization fit
+    let runs: Option<Vec<MixtureRun>> = (0..n_init)
+        .into_par_iter()
+        .map(|run| fit_single(&data_array, &settings, random_seed.map(|seed| seed.wrapping_add(run as i64))))
+        .collect();
+    let best = runs
+        .ok_or_else(ill_defined_covariance)?
+        .into_iter()
+        .reduce(|best, run| if run.lower_bound > best.lower_bound { run } else { best })
+        .expect("n_init is at least 1");
+
+    // A final E-step keeps labels consistent with the returned parameters
+    let (_, log_resp) = best.params.e_step(&data_array).ok_or_else(ill_defined_covariance)?;
+
+    let result = RHash::new();
+    result.aset("labels", RArray::from_vec(argmax_rows(&log_resp)))?;
+    result.aset("weights", RArray::from_vec(best.params.weights.clone()))?;
+    result.aset("means", matrix_to_ruby(&best.params.means)?)?;
+    let covariances = match &best.params.covariances {
+        Covariances::Full(matrices) => {
+            let all = RArray::with_capacity(matrices.len());
+            for matrix in matrices {
+                all.push(matrix_to_ruby(matrix)?)?;
+            }
+            all
+        }
+        Covariances::Diag(variances) => matrix_to_ruby(variances)?,
+        Covariances::Spherical(variances) => RArray::from_vec(variances.clone()),
+    };
+    result.aset("covariances", covariances)?;
+    result.aset("converged", best.converged)?;
+    result.aset("n_iter", best.n_iter)?;
+    result.aset("lower_bound", best.lower_bound)?;
+    result.aset("model", GaussianMixtureModel { params: best.params })?;
+
+    Ok(result)
+}
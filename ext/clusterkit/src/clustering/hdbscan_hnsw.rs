@@ -0,0 +1,167 @@
+use ndarray::Array2;
+use rayon::prelude::*;
+use super::hdbscan_tree::{HdbscanHyperParams, HdbscanTree};
+use super::knn::KnnIndex;
+use super::metric::Metric;
+
+/// Members of a component the bridging search starts from
+const BRIDGE_SEEDS: usize = 16;
+
+/// Neighbors asked for in the first bridging search, doubled while every one
+/// found lies inside the component, up to BRIDGE_MAX_CANDIDATES
+const BRIDGE_CANDIDATES: usize = 16;
+const BRIDGE_MAX_CANDIDATES: usize = 1024;
+
+/// Medoid candidates per cluster, keeping medoids linear in the cluster size
+pub(super) const MEDOID_CANDIDATES: usize = 256;
+
+/// Run HDBSCAN with core distances and the mutual reachability MST taken from
+/// an approximate `n_neighbors`-nearest-neighbor graph built with HNSW, instead
+/// of from all pairwise distances. Memory and time grow with n * n_neighbors
+/// rather than n², at the cost of an approximate hierarchy
+pub(super) fn fit(
+    data: &Array2<f64>,
+    metric: Metric,
+    params: &HdbscanHyperParams,
+    n_neighbors: usize,
+    random_seed: Option<i64>,
+) -> HdbscanTree {
+    let n_samples = data.nrows();
+    let k = n_neighbors.max(params.min_samples).min(n_samples);
+    let distance = metric.pairwise(data);
+
+    let index = KnnIndex::build(data, random_seed.map(|seed| seed as u64));
+    let neighbors = index.knn_graph(metric, k, &distance);
+    let core: Vec<f64> = neighbors
+        .iter()
+        .map(|list| list[params.min_samples.min(list.len()) - 1].1)
+        .collect();
+    let mst = knn_mst(&neighbors, &core, &distance, params.alpha, &index);
+
+    HdbscanTree::from_mst(core, mst, params)
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Spanning tree of the mutual reachability graph restricted to the k-NN
+/// edges (Kruskal). Components the k-NN graph leaves apart are then joined in
+/// Borůvka passes: every component but the largest searches the index from a
+/// few of its members, widening the search until it reaches points outside,
+/// and bridges to the closest of those. A component the widest search cannot
+/// leave is bridged from one member by a scan
+fn knn_mst<F>(
+    neighbors: &[Vec<(usize, f64)>],
+    core: &[f64],
+    distance: &F,
+    alpha: f64,
+    index: &KnnIndex,
+) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    let n_samples = core.len();
+    let reachability = |i: usize, j: usize, d: f64| (d / alpha).max(core[i]).max(core[j]);
+
+    // Both directions of an edge have the same weight, so they end up adjacent
+    let mut edges: Vec<(usize, usize, f64)> = neighbors
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, list)| {
+            list.iter()
+                .filter(move |&&(j, _)| j != i)
+                .map(move |&(j, d)| (i.min(j), i.max(j), reachability(i, j, d)))
+        })
+        .collect();
+    edges.par_sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    edges.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+    let mut parent: Vec<usize> = (0..n_samples).collect();
+    let mut mst = Vec::with_capacity(n_samples.saturating_sub(1));
+    for (i, j, weight) in edges {
+        let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+        if root_i != root_j {
+            parent[root_j] = root_i;
+            mst.push((i, j, weight));
+        }
+    }
+
+    while mst.len() + 1 < n_samples {
+        let mut components: Vec<Vec<usize>> = vec![Vec::new(); n_samples];
+        let mut component_of = vec![0usize; n_samples];
+        for (point, component) in component_of.iter_mut().enumerate() {
+            let root = find(&mut parent, point);
+            components[root].push(point);
+            *component = root;
+        }
+        components.retain(|members| !members.is_empty());
+        let largest = (0..components.len())
+            .reduce(|best, c| if components[c].len() > components[best].len() { c } else { best })
+            .expect("at least two components");
+
+        // Every component but the largest finds its way out, so the searches
+        // cover at most half the points; ties keep the lowest indices
+        let mut bridges: Vec<(usize, usize, f64)> = components
+            .par_iter()
+            .enumerate()
+            .filter(|&(c, _)| c != largest)
+            .map(|(_, members)| {
+                let root = component_of[members[0]];
+                let stride = members.len().div_ceil(BRIDGE_SEEDS);
+                let seeds: Vec<usize> = members.iter().copied().step_by(stride).collect();
+                let queries: Vec<Vec<f32>> = seeds.iter().map(|&i| index.points[i].clone()).collect();
+
+                let max_k = BRIDGE_MAX_CANDIDATES.min(n_samples);
+                let mut k = BRIDGE_CANDIDATES.min(max_k);
+                loop {
+                    let best = seeds
+                        .iter()
+                        .zip(index.search(&queries, k))
+                        .flat_map(|(&from, found)| {
+                            found
+                                .into_iter()
+                                .filter(|&to| component_of[to] != root)
+                                .map(move |to| (from.min(to), from.max(to), reachability(from, to, distance(from, to))))
+                        })
+                        .reduce(|best, candidate| {
+                            if candidate.2.total_cmp(&best.2).then((candidate.0, candidate.1).cmp(&(best.0, best.1))).is_lt() {
+                                candidate
+                            } else {
+                                best
+                            }
+                        });
+                    if let Some(bridge) = best {
+                        break bridge;
+                    }
+                    if k == max_k {
+                        // Still enclosed, or the index missed every outside point; fall back to a scan
+                        let from = members[0];
+                        let (to, weight) = (0..n_samples)
+                            .filter(|&to| component_of[to] != root)
+                            .map(|to| (to, reachability(from, to, distance(from, to))))
+                            .reduce(|best, candidate| if candidate.1 < best.1 { candidate } else { best })
+                            .expect("another component exists");
+                        break (from.min(to), from.max(to), weight);
+                    }
+                    k = (2 * k).min(max_k);
+                }
+            })
+            .collect();
+        bridges.sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+
+        for (i, j, weight) in bridges {
+            let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+            if root_i != root_j {
+                parent[root_j] = root_i;
+                mst.push((i, j, weight));
+            }
+        }
+    }
+
+    mst
+}
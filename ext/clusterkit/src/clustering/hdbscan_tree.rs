@@ -0,0 +1,663 @@
+use magnus::Error;
+use ndarray::Array2;
+use rayon::prelude::*;
+use std::collections::VecDeque;
+use super::linkage::{linkage_from_merges, minimum_spanning_tree, LinkageRow};
+use super::metric::Metric;
+
+/// HDBSCAN hyperparameters
+pub(super) struct HdbscanHyperParams {
+    /// Neighborhood size (including the point itself) defining core distances
+    pub(super) min_samples: usize,
+    /// Smallest group of points the condensed tree treats as a cluster
+    pub(super) min_cluster_size: usize,
+    /// Clusters born below this distance are merged into their ancestors (0 disables)
+    pub(super) cluster_selection_epsilon: f64,
+    pub(super) cluster_selection_method: ClusterSelectionMethod,
+    /// Let the root be selected, so the whole dataset can form one cluster
+    pub(super) allow_single_cluster: bool,
+    /// Clusters larger than this are never selected with excess of mass
+    pub(super) max_cluster_size: Option<usize>,
+    /// Divides distances (but not core distances) in the mutual reachability
+    pub(super) alpha: f64,
+}
+
+/// How clusters are picked from the condensed tree
+#[derive(Clone, Copy)]
+pub(super) enum ClusterSelectionMethod {
+    /// Excess of mass: the most stable clusters, favoring a few large ones
+    ExcessOfMass,
+    /// The leaves of the condensed tree: many small homogeneous clusters
+    Leaf,
+}
+
+impl ClusterSelectionMethod {
+    pub(super) fn parse(method: &str) -> Result<Self, Error> {
+        match method {
+            "eom" => Ok(ClusterSelectionMethod::ExcessOfMass),
+            "leaf" => Ok(ClusterSelectionMethod::Leaf),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown cluster selection method: {} (expected eom or leaf)", other),
+            )),
+        }
+    }
+}
+
+/// Row of the condensed tree: `child` (a point below n_samples or a cluster
+/// id from n_samples up, the root being n_samples) leaves `parent` at
+/// `lambda` = 1 / distance, carrying `size` points
+#[derive(Clone, Copy)]
+pub(super) struct CondensedRow {
+    pub(super) parent: usize,
+    pub(super) child: usize,
+    pub(super) lambda: f64,
+    pub(super) size: usize,
+}
+
+/// Fitted HDBSCAN hierarchy and the flat clustering extracted from it
+pub(super) struct HdbscanTree {
+    pub(super) n_samples: usize,
+    /// Distance from each point to its `min_samples`-th nearest neighbor
+    pub(super) core_distances: Vec<f64>,
+    pub(super) linkage: Vec<LinkageRow>,
+    pub(super) condensed: Vec<CondensedRow>,
+    /// Excess of mass per condensed cluster, indexed by cluster id - n_samples
+    pub(super) stability: Vec<f64>,
+    /// Selected cluster ids in label order
+    pub(super) clusters: Vec<usize>,
+    pub(super) labels: Vec<i64>,
+    pub(super) probabilities: Vec<f64>,
+    pub(super) outlier_scores: Vec<f64>,
+}
+
+/// Run HDBSCAN on data prepared for `metric`
+pub(super) fn fit(data: &Array2<f64>, metric: Metric, params: &HdbscanHyperParams) -> HdbscanTree {
+    let distance = metric.pairwise(data);
+    let core = core_distances(data.nrows(), params.min_samples, &distance);
+    let mst = mutual_reachability_mst(&core, &|i, j| distance(i, j) / params.alpha);
+    HdbscanTree::from_mst(core, mst, params)
+}
+
+/// Distance from each point to its `min_samples`-th nearest neighbor,
+/// counting the point itself as the first
+fn core_distances<F>(n_samples: usize, min_samples: usize, distance: &F) -> Vec<f64>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    (0..n_samples)
+        .into_par_iter()
+        .map(|i| {
+            let mut distances: Vec<f64> = (0..n_samples).map(|j| distance(i, j)).collect();
+            let kth = min_samples.clamp(1, n_samples) - 1;
+            *distances.select_nth_unstable_by(kth, |a, b| a.total_cmp(b)).1
+        })
+        .collect()
+}
+
+/// Minimum spanning tree of the mutual reachability graph, where the weight of
+/// (i, j) is max(core_i, core_j, distance(i, j))
+fn mutual_reachability_mst<F>(core: &[f64], distance: &F) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    minimum_spanning_tree(core.len(), &|i, j| distance(i, j).max(core[i]).max(core[j]))
+}
+
+impl HdbscanTree {
+    /// Build the hierarchy from the mutual reachability MST and extract clusters
+    pub(super) fn from_mst(core_distances: Vec<f64>, mst: Vec<(usize, usize, f64)>, params: &HdbscanHyperParams) -> Self {
+        let n_samples = core_distances.len();
+        let linkage = linkage_from_merges(n_samples, mst);
+        let condensed = condense(n_samples, &linkage, params.min_cluster_size);
+        let stability = stabilities(n_samples, &condensed);
+
+        let mut tree = HdbscanTree {
+            n_samples,
+            core_distances,
+            linkage,
+            condensed,
+            stability,
+            clusters: Vec::new(),
+            labels: Vec::new(),
+            probabilities: Vec::new(),
+            outlier_scores: Vec::new(),
+        };
+        tree.clusters = tree.select_clusters(params);
+        tree.labels = tree.label_points(params);
+        // Only a lone root can end up without members; then there is no cluster at all
+        if tree.labels.iter().all(|&label| label < 0) {
+            tree.clusters.clear();
+        }
+        tree.probabilities = tree.membership_probabilities();
+        tree.outlier_scores = tree.glosh();
+        tree
+    }
+
+    fn n_clusters_in_tree(&self) -> usize {
+        self.stability.len()
+    }
+
+    /// Parent of every cluster (root maps to itself), indexed by id - n_samples
+    fn cluster_parents(&self) -> Vec<usize> {
+        let mut parents = vec![self.n_samples; self.n_clusters_in_tree()];
+        for row in self.condensed.iter().filter(|row| row.child >= self.n_samples) {
+            parents[row.child - self.n_samples] = row.parent;
+        }
+        parents
+    }
+
+    /// Child clusters of every cluster, indexed by id - n_samples
+    fn cluster_children(&self) -> Vec<Vec<usize>> {
+        let mut children = vec![Vec::new(); self.n_clusters_in_tree()];
+        for row in self.condensed.iter().filter(|row| row.child >= self.n_samples) {
+            children[row.parent - self.n_samples].push(row.child);
+        }
+        children
+    }
+
+    /// Points in every cluster, indexed by id - n_samples
+    fn cluster_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![self.n_samples; self.n_clusters_in_tree()];
+        for row in self.condensed.iter().filter(|row| row.child >= self.n_samples) {
+            sizes[row.child - self.n_samples] = row.size;
+        }
+        sizes
+    }
+
+    /// Lambda at which every cluster splits off its parent (0 for the root),
+    /// indexed by id - n_samples
+    fn birth_lambdas(&self) -> Vec<f64> {
+        let mut births = vec![0.0f64; self.n_clusters_in_tree()];
+        for row in self.condensed.iter().filter(|row| row.child >= self.n_samples) {
+            births[row.child - self.n_samples] = row.lambda;
+        }
+        births
+    }
+
+    /// Pick the flat clustering from the condensed tree, then merge clusters
+    /// that split off below `cluster_selection_epsilon`. The root is only a
+    /// candidate with `allow_single_cluster`
+    fn select_clusters(&self, params: &HdbscanHyperParams) -> Vec<usize> {
+        let n = self.n_samples;
+        let children = self.cluster_children();
+        let first = if params.allow_single_cluster { 0 } else { 1 };
+        let mut selected = vec![false; self.n_clusters_in_tree()];
+
+        match params.cluster_selection_method {
+            // Keep a cluster unless its descendants are jointly more stable or it
+            // exceeds max_cluster_size
+            ClusterSelectionMethod::ExcessOfMass => {
+                let sizes = self.cluster_sizes();
+                let max_size = params.max_cluster_size.unwrap_or(usize::MAX);
+                let mut stability = self.stability.clone();
+
+                // Children have larger ids than their parents, so this is bottom-up
+                for cluster in (first..self.n_clusters_in_tree()).rev() {
+                    let subtree: f64 = children[cluster].iter().map(|&child| stability[child - n]).sum();
+                    if (!children[cluster].is_empty() && subtree > stability[cluster]) || sizes[cluster] > max_size {
+                        stability[cluster] = subtree;
+                    } else {
+                        selected[cluster] = true;
+                        let mut stack = children[cluster].clone();
+                        while let Some(descendant) = stack.pop() {
+                            selected[descendant - n] = false;
+                            stack.extend(&children[descendant - n]);
+                        }
+                    }
+                }
+            }
+            ClusterSelectionMethod::Leaf => {
+                for cluster in first..self.n_clusters_in_tree() {
+                    selected[cluster] = children[cluster].is_empty();
+                }
+            }
+        }
+
+        // A lone root stays as it is; epsilon only merges clusters below it
+        if params.cluster_selection_epsilon > 0.0 && !selected[0] {
+            selected = self.epsilon_merge(selected, params);
+        }
+
+        (0..selected.len()).filter(|&c| selected[c]).map(|c| c + n).collect()
+    }
+
+    /// Replace every selected cluster that splits off at a distance below
+    /// `cluster_selection_epsilon` with its closest ancestor that splits off
+    /// above it (Malzer and Baum 2020). Ancestors stop below the root unless
+    /// `allow_single_cluster` is set
+    fn epsilon_merge(&self, selected: Vec<bool>, params: &HdbscanHyperParams) -> Vec<bool> {
+        let n = self.n_samples;
+        let parents = self.cluster_parents();
+        let births = self.birth_lambdas();
+        let max_lambda = 1.0 / params.cluster_selection_epsilon;
+
+        let mut merged = vec![false; selected.len()];
+        for mut cluster in (1..selected.len()).filter(|&c| selected[c]) {
+            if births[cluster] > max_lambda {
+                loop {
+                    let parent = parents[cluster] - n;
+                    if parent == 0 {
+                        if params.allow_single_cluster {
+                            cluster = 0;
+                        }
+                        break;
+                    }
+                    cluster = parent;
+                    if births[parent] < max_lambda {
+                        break;
+                    }
+                }
+            }
+            merged[cluster] = true;
+        }
+
+        // Drop clusters whose ancestor was selected as well
+        let mut covered = vec![false; merged.len()];
+        for cluster in 1..merged.len() {
+            let parent = parents[cluster] - n;
+            if covered[parent] || merged[parent] {
+                covered[cluster] = true;
+                merged[cluster] = false;
+            }
+        }
+        merged
+    }
+
+    /// Label of the selected cluster each condensed cluster belongs to, if any,
+    /// indexed by id - n_samples
+    fn cluster_labels(&self) -> Vec<Option<usize>> {
+        let n = self.n_samples;
+        let parents = self.cluster_parents();
+        let mut labels: Vec<Option<usize>> = vec![None; self.n_clusters_in_tree()];
+        for (label, &cluster) in self.clusters.iter().enumerate() {
+            labels[cluster - n] = Some(label);
+        }
+        // Parents come before children, so labels flow down the tree
+        for cluster in 1..labels.len() {
+            if labels[cluster].is_none() {
+                labels[cluster] = labels[parents[cluster] - n];
+            }
+        }
+        labels
+    }
+
+    /// Rows where each point falls out of the tree, indexed by point
+    fn point_rows(&self) -> Vec<CondensedRow> {
+        let mut rows = vec![CondensedRow { parent: self.n_samples, child: 0, lambda: 0.0, size: 1 }; self.n_samples];
+        for row in self.condensed.iter().filter(|row| row.child < self.n_samples) {
+            rows[row.child] = *row;
+        }
+        rows
+    }
+
+    /// Label of the selected cluster each point falls out of, -1 for noise
+    /// When the root is the single selected cluster, only points that persist
+    /// to its last split (or to `cluster_selection_epsilon`) belong to it
+    fn label_points(&self, params: &HdbscanHyperParams) -> Vec<i64> {
+        let cluster_labels = self.cluster_labels();
+        let root_threshold = if params.cluster_selection_epsilon > 0.0 {
+            1.0 / params.cluster_selection_epsilon
+        } else {
+            self.death_lambdas()[0]
+        };
+        let root_selected = self.clusters.first() == Some(&self.n_samples);
+
+        self.point_rows()
+            .iter()
+            .map(|row| {
+                if root_selected && row.lambda < root_threshold {
+                    return -1;
+                }
+                cluster_labels[row.parent - self.n_samples].map_or(-1, |label| label as i64)
+            })
+            .collect()
+    }
+
+    /// Stability of each selected cluster, in label order
+    pub(super) fn cluster_stability(&self) -> Vec<f64> {
+        self.clusters.iter().map(|&cluster| self.stability[cluster - self.n_samples]).collect()
+    }
+
+    /// Persistence of each selected cluster in [0, 1], in label order: its
+    /// stability relative to the most a cluster of its size could reach,
+    /// with every point persisting from birth to the largest lambda in the tree
+    pub(super) fn cluster_persistence(&self) -> Vec<f64> {
+        let max_lambda = lambda_cap(&self.condensed);
+        let mut sizes = vec![0usize; self.clusters.len()];
+        for &label in self.labels.iter().filter(|&&label| label >= 0) {
+            sizes[label as usize] += 1;
+        }
+
+        self.cluster_stability()
+            .iter()
+            .zip(&sizes)
+            .map(|(&stability, &size)| {
+                if size == 0 || max_lambda == 0.0 {
+                    1.0
+                } else {
+                    (stability / (size as f64 * max_lambda)).min(1.0)
+                }
+            })
+            .collect()
+    }
+
+    /// Largest lambda among the rows leaving each cluster directly, indexed
+    /// by id - n_samples
+    fn death_lambdas(&self) -> Vec<f64> {
+        let mut deaths = vec![0.0f64; self.n_clusters_in_tree()];
+        for row in &self.condensed {
+            let death = &mut deaths[row.parent - self.n_samples];
+            *death = death.max(row.lambda);
+        }
+        deaths
+    }
+
+    /// Strength of each point's membership: the lambda at which it left its
+    /// cluster relative to the cluster's largest lambda. Noise scores 0
+    fn membership_probabilities(&self) -> Vec<f64> {
+        let deaths = self.death_lambdas();
+        self.point_rows()
+            .iter()
+            .zip(&self.labels)
+            .map(|(row, &label)| {
+                if label < 0 {
+                    return 0.0;
+                }
+                let max_lambda = deaths[self.clusters[label as usize] - self.n_samples];
+                let lambda = row.lambda.min(max_lambda);
+                if max_lambda == 0.0 || !lambda.is_finite() {
+                    1.0
+                } else {
+                    lambda / max_lambda
+                }
+            })
+            .collect()
+    }
+
+    /// Points of each selected cluster, in label order
+    fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.clusters.len()];
+        for (point, &label) in self.labels.iter().enumerate().filter(|(_, &label)| label >= 0) {
+            members[label as usize].push(point);
+        }
+        members
+    }
+
+    /// Mean of each selected cluster weighted by membership probability, in
+    /// label order
+    pub(super) fn centroids(&self, data: &Array2<f64>) -> Array2<f64> {
+        let mut centroids = Array2::zeros((self.clusters.len(), data.ncols()));
+        for (label, points) in self.members().iter().enumerate() {
+            let weight: f64 = points.iter().map(|&point| self.probabilities[point]).sum();
+            let mut centroid = centroids.row_mut(label);
+            for &point in points {
+                // Members that all have zero probability fall back to an unweighted mean
+                let share = if weight > 0.0 { self.probabilities[point] / weight } else { 1.0 / points.len() as f64 };
+                centroid.scaled_add(share, &data.row(point));
+            }
+        }
+        centroids
+    }
+
+    /// Member of each selected cluster with the smallest probability-weighted
+    /// distance to the other members, in label order. Ties go to the lowest index
+    /// Only the `max_candidates` members with the highest membership probability
+    /// are considered, which bounds the cost at max_candidates * cluster size
+    pub(super) fn medoids<F>(&self, distance: &F, max_candidates: usize) -> Vec<usize>
+    where
+        F: Fn(usize, usize) -> f64 + Sync,
+    {
+        self.members()
+            .iter()
+            .map(|points| {
+                let mut candidates = points.clone();
+                if candidates.len() > max_candidates {
+                    candidates.sort_by(|&a, &b| self.probabilities[b].total_cmp(&self.probabilities[a]).then(a.cmp(&b)));
+                    candidates.truncate(max_candidates);
+                    candidates.sort_unstable();
+                }
+
+                let costs: Vec<f64> = candidates
+                    .par_iter()
+                    .map(|&i| points.iter().map(|&j| self.probabilities[j] * distance(i, j)).sum())
+                    .collect();
+                let best = (0..candidates.len())
+                    .reduce(|best, i| if costs[i] < costs[best] { i } else { best })
+                    .expect("selected clusters have members");
+                candidates[best]
+            })
+            .collect()
+    }
+
+    /// Exemplars of each selected cluster, in label order: the points that
+    /// persist longest in the leaf clusters below it, i.e. those leaving a
+    /// leaf at its largest lambda
+    pub(super) fn exemplars(&self) -> Vec<Vec<usize>> {
+        let n = self.n_samples;
+        let children = self.cluster_children();
+        let deaths = self.death_lambdas();
+        let mut members = vec![Vec::new(); self.n_clusters_in_tree()];
+        for row in self.condensed.iter().filter(|row| row.child < n) {
+            members[row.parent - n].push(*row);
+        }
+
+        self.clusters
+            .iter()
+            .map(|&cluster| {
+                let mut exemplars = Vec::new();
+                let mut stack = vec![cluster];
+                while let Some(cluster) = stack.pop() {
+                    let index = cluster - n;
+                    if children[index].is_empty() {
+                        exemplars.extend(
+                            members[index].iter().filter(|row| row.lambda == deaths[index]).map(|row| row.child),
+                        );
+                    } else {
+                        stack.extend(&children[index]);
+                    }
+                }
+                exemplars.sort_unstable();
+                exemplars
+            })
+            .collect()
+    }
+
+    /// Place `n_points` new points in the fitted hierarchy without changing it,
+    /// following the approximate_predict of McInnes et al.'s hdbscan: each new
+    /// point joins the tree next to its nearest training point under mutual
+    /// reachability, at lambda = 1 / that distance, and takes the label of the
+    /// selected cluster it lands in. `distance(i, j)` is the distance from new
+    /// point i to training point j
+    /// Returns a label (-1 for noise) and membership probability per point
+    pub(super) fn approximate_predict<F>(&self, n_points: usize, params: &HdbscanHyperParams, distance: F) -> Vec<(i64, f64)>
+    where
+        F: Fn(usize, usize) -> f64 + Sync,
+    {
+        let n = self.n_samples;
+        let parents = self.cluster_parents();
+        let cluster_labels = self.cluster_labels();
+        let deaths = self.death_lambdas();
+        let point_rows = self.point_rows();
+        let births = self.birth_lambdas();
+        let min_samples = params.min_samples;
+
+        (0..n_points)
+            .into_par_iter()
+            .map(|i| {
+                let distances: Vec<f64> = (0..n).map(|j| distance(i, j)).collect();
+
+                // The new point counts itself as the first of its min_samples neighbors
+                let core = if min_samples > 1 && n > 0 {
+                    let mut sorted = distances.clone();
+                    let kth = (min_samples - 2).min(n - 1);
+                    *sorted.select_nth_unstable_by(kth, |a, b| a.total_cmp(b)).1
+                } else {
+                    0.0
+                };
+
+                // Nearest training point under mutual reachability; ties go to the lowest index
+                let Some((neighbor, reachability)) = distances
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &d)| (j, (d / params.alpha).max(core).max(self.core_distances[j])))
+                    .reduce(|best, candidate| if candidate.1 < best.1 { candidate } else { best })
+                else {
+                    return (-1, 0.0);
+                };
+
+                // The point cannot join the tree later than its neighbor left it
+                let row = point_rows[neighbor];
+                let lambda = if reachability > 0.0 { 1.0 / reachability } else { f64::INFINITY };
+                let lambda = lambda.min(row.lambda);
+
+                // Climb to the cluster that already existed at that lambda
+                let mut cluster = row.parent;
+                while cluster > n && births[cluster - n] >= lambda {
+                    cluster = parents[cluster - n];
+                }
+
+                match cluster_labels[cluster - n] {
+                    Some(label) => {
+                        let max_lambda = deaths[self.clusters[label] - n];
+                        let lambda = lambda.min(max_lambda);
+                        let probability = if max_lambda == 0.0 || !lambda.is_finite() { 1.0 } else { lambda / max_lambda };
+                        (label as i64, probability)
+                    }
+                    None => (-1, 0.0),
+                }
+            })
+            .collect()
+    }
+
+    /// GLOSH outlier scores (Campello et al. 2015): 1 - lambda_point / lambda_max,
+    /// where lambda_max is the largest lambda anywhere below the point's cluster
+    fn glosh(&self) -> Vec<f64> {
+        let n = self.n_samples;
+        let parents = self.cluster_parents();
+        let mut deaths = self.death_lambdas();
+        for cluster in (1..deaths.len()).rev() {
+            let parent = parents[cluster] - n;
+            deaths[parent] = deaths[parent].max(deaths[cluster]);
+        }
+
+        self.point_rows()
+            .iter()
+            .map(|row| {
+                let max_lambda = deaths[row.parent - n];
+                if max_lambda == 0.0 || !row.lambda.is_finite() {
+                    0.0
+                } else if !max_lambda.is_finite() {
+                    1.0
+                } else {
+                    (max_lambda - row.lambda) / max_lambda
+                }
+            })
+            .collect()
+    }
+}
+
+/// Condense the single-linkage tree: walking down from the root, a split
+/// only creates two new clusters when both sides have at least
+/// `min_cluster_size` points; smaller sides fall out of their parent as points
+fn condense(n_samples: usize, linkage: &[LinkageRow], min_cluster_size: usize) -> Vec<CondensedRow> {
+    let mut rows = Vec::new();
+    if n_samples < 2 {
+        rows.extend((0..n_samples).map(|point| CondensedRow { parent: n_samples, child: point, lambda: 0.0, size: 1 }));
+        return rows;
+    }
+
+    let size = |node: usize| if node < n_samples { 1 } else { linkage[node - n_samples].size };
+    let points_below = |node: usize| {
+        let mut points = Vec::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if node < n_samples {
+                points.push(node);
+            } else {
+                let row = &linkage[node - n_samples];
+                stack.push(row.right);
+                stack.push(row.left);
+            }
+        }
+        points
+    };
+
+    let root = 2 * n_samples - 2;
+    let mut relabel = vec![0usize; 2 * n_samples - 1];
+    relabel[root] = n_samples;
+    let mut next_label = n_samples + 1;
+
+    let mut queue = VecDeque::from([root]);
+    while let Some(node) = queue.pop_front() {
+        let merge = linkage[node - n_samples];
+        let parent = relabel[node];
+        let lambda = if merge.distance > 0.0 { 1.0 / merge.distance } else { f64::INFINITY };
+
+        let fall_out = |child: usize, rows: &mut Vec<CondensedRow>| {
+            rows.extend(points_below(child).into_iter().map(|point| CondensedRow { parent, child: point, lambda, size: 1 }));
+        };
+
+        let (left, right) = (merge.left, merge.right);
+        match (size(left) >= min_cluster_size, size(right) >= min_cluster_size) {
+            (true, true) => {
+                for child in [left, right] {
+                    relabel[child] = next_label;
+                    rows.push(CondensedRow { parent, child: next_label, lambda, size: size(child) });
+                    next_label += 1;
+                    queue.push_back(child);
+                }
+            }
+            (true, false) => {
+                relabel[left] = parent;
+                queue.push_back(left);
+                fall_out(right, &mut rows);
+            }
+            (false, true) => {
+                relabel[right] = parent;
+                queue.push_back(right);
+                fall_out(left, &mut rows);
+            }
+            (false, false) => {
+                fall_out(left, &mut rows);
+                fall_out(right, &mut rows);
+            }
+        }
+    }
+
+    rows
+}
+
+/// Excess of mass of every condensed cluster: the sum over its points of
+/// (lambda at which they leave - lambda at which the cluster was born)
+/// Zero distances give infinite lambdas, which are capped at the largest
+/// finite lambda so stabilities stay comparable
+fn stabilities(n_samples: usize, condensed: &[CondensedRow]) -> Vec<f64> {
+    // Cluster ids are contiguous from the root, which is nobody's child
+    let n_clusters = 1 + condensed.iter().filter(|row| row.child >= n_samples).count();
+
+    let cap = lambda_cap(condensed);
+    let finite = |lambda: f64| lambda.min(cap);
+
+    let mut births = vec![0.0f64; n_clusters];
+    for row in condensed.iter().filter(|row| row.child >= n_samples) {
+        births[row.child - n_samples] = finite(row.lambda);
+    }
+
+    let mut stability = vec![0.0f64; n_clusters];
+    for row in condensed {
+        let cluster = row.parent - n_samples;
+        stability[cluster] += (finite(row.lambda) - births[cluster]) * row.size as f64;
+    }
+    stability
+}
+
+/// Largest finite lambda in the condensed tree (1.0 if there is none)
+fn lambda_cap(condensed: &[CondensedRow]) -> f64 {
+    condensed
+        .iter()
+        .map(|row| row.lambda)
+        .filter(|lambda| lambda.is_finite())
+        .fold(None, |max: Option<f64>, lambda| Some(max.map_or(lambda, |max| max.max(lambda))))
+        .unwrap_or(1.0)
+}
@@ -1,84 +1,230 @@
-use magnus::{function, prelude::*, Error, Value, RArray, RHash, Integer};
-use hdbscan::{Hdbscan, HdbscanHyperParams};
-use crate::utils::ruby_array_to_vec_vec_f64;
+use magnus::{class, function, method, prelude::*, Error, Value, RArray, RHash};
+use ndarray::Array2;
+use crate::errors::dimension_mismatch_error;
+use crate::utils::ruby_array_to_ndarray;
+use super::hdbscan_hnsw;
+use super::hdbscan_tree::{self, ClusterSelectionMethod, HdbscanHyperParams, HdbscanTree};
+use super::metric::Metric;
+
+/// Fitted HDBSCAN state kept on the Ruby side to place new points:
+/// the training data, core distances and condensed tree, and the exemplars
+/// of each cluster
+#[magnus::wrap(class = "ClusterKit::Clustering::HdbscanModel", free_immediately, size)]
+struct HdbscanModel {
+    /// Prepared training data; not kept for precomputed distances
+    data: Option<Array2<f64>>,
+    metric: Metric,
+    params: HdbscanHyperParams,
+    tree: HdbscanTree,
+    exemplars: Vec<Vec<usize>>,
+}
+
+impl HdbscanModel {
+    /// Label and membership probability of new points, without refitting
+    /// For the precomputed metric `data` holds the distances from each new
+    /// point to every training point
+    /// Returns a hash with labels (-1 for noise) and probabilities
+    fn approximate_predict(&self, data: Value) -> Result<RHash, Error> {
+        let points = match self.metric {
+            Metric::Precomputed => ruby_array_to_ndarray::<f64>(data)?,
+            metric => metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?,
+        };
+        let expected = match &self.data {
+            Some(training) => training.ncols(),
+            None => self.tree.n_samples,
+        };
+        if points.ncols() != expected {
+            return Err(dimension_mismatch_error(match self.data {
+                Some(_) => format!("Data has {} features but the model was fitted on {}", points.ncols(), expected),
+                None => format!("Distance rows have {} entries but the model was fitted on {} samples", points.ncols(), expected),
+            }));
+        }
+
+        let predictions = match &self.data {
+            Some(training) => self.tree.approximate_predict(points.nrows(), &self.params, |i, j| {
+                self.metric.distance(&points.row(i), &training.row(j))
+            }),
+            None => self.tree.approximate_predict(points.nrows(), &self.params, |i, j| points[[i, j]]),
+        };
+        let (labels, probabilities): (Vec<i64>, Vec<f64>) = predictions.into_iter().unzip();
+
+        let result = RHash::new();
+        result.aset("labels", RArray::from_vec(labels))?;
+        result.aset("probabilities", RArray::from_vec(probabilities))?;
+        Ok(result)
+    }
+
+    /// Indices of the exemplar points of each cluster, in label order
+    fn exemplars(&self) -> Result<RArray, Error> {
+        let exemplars = RArray::with_capacity(self.exemplars.len());
+        for points in &self.exemplars {
+            exemplars.push(RArray::from_vec(points.clone()))?;
+        }
+        Ok(exemplars)
+    }
+}
 
 /// Perform HDBSCAN clustering
-/// Returns a hash with labels and basic statistics
+/// `metric` is "euclidean" (or "l2"), "manhattan" (or "l1"), "cosine", "chebyshev"
+/// or "precomputed", in which case `data` is an n x n distance matrix
+/// `cluster_selection_method` is "eom" (excess of mass) or "leaf"; a nil
+/// `max_cluster_size` means no limit
+/// `algorithm` is "exact" (all pairwise distances, O(n²) time) or "hnsw", which
+/// takes core distances and the spanning tree from an approximate
+/// `n_neighbors`-nearest-neighbor graph for large datasets; `random_seed` makes
+/// that graph reproducible
+/// Returns a hash with labels, membership probabilities, GLOSH outlier scores,
+/// per-cluster persistence and stability, the condensed and single-linkage trees,
+/// per-cluster centroids (weighted by membership probability; nil for precomputed
+/// distances), medoid and exemplar point indices, the fitted model for approximate
+/// prediction, and the parameters that had to be adjusted to fit the data as
+/// name => [requested, used]
+#[allow(clippy::too_many_arguments)]
 pub fn hdbscan_fit(
     data: Value,
     min_samples: usize,
     min_cluster_size: usize,
     metric: String,
+    cluster_selection_epsilon: f64,
+    cluster_selection_method: String,
+    allow_single_cluster: bool,
+    max_cluster_size: Option<usize>,
+    alpha: f64,
+    algorithm: String,
+    n_neighbors: usize,
+    random_seed: Option<i64>,
 ) -> Result<RHash, Error> {
-    // Convert Ruby array to Vec<Vec<f64>> using shared helper
-    let data_vec = ruby_array_to_vec_vec_f64(data)?;
-    let n_samples = data_vec.len();
-    
-    // Note: hdbscan crate doesn't support custom metrics directly
-    // We'll use the default Euclidean distance for now
-    if metric != "euclidean" && metric != "l2" {
-        eprintln!("Warning: Current hdbscan version only supports Euclidean distance. Using Euclidean.");
+    let metric = Metric::parse(&metric)?;
+    let cluster_selection_method = ClusterSelectionMethod::parse(&cluster_selection_method)?;
+
+    let use_hnsw = match algorithm.as_str() {
+        "exact" => false,
+        "hnsw" => true,
+        other => {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown HDBSCAN algorithm: {} (expected exact or hnsw)", other),
+            ));
+        }
+    };
+
+    if use_hnsw && matches!(metric, Metric::Precomputed) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "The hnsw algorithm needs data points, not a precomputed distance matrix",
+        ));
+    }
+
+    if !(cluster_selection_epsilon >= 0.0 && cluster_selection_epsilon.is_finite()) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("cluster_selection_epsilon must be non-negative (got {})", cluster_selection_epsilon),
+        ));
+    }
+
+    if !(alpha > 0.0 && alpha.is_finite()) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("alpha must be positive (got {})", alpha),
+        ));
     }
-    
-    // Adjust parameters to avoid index out of bounds errors
-    // The hdbscan crate has issues when min_samples >= n_samples
+
+    let data_array = metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?;
+    let n_samples = data_array.nrows();
+
+    // Keep parameters within what the data can support, and say so
+    let adjusted_parameters = RHash::new();
     let adjusted_min_samples = min_samples.min(n_samples.saturating_sub(1)).max(1);
+    if adjusted_min_samples != min_samples {
+        adjusted_parameters.aset("min_samples", (min_samples, adjusted_min_samples))?;
+    }
     let adjusted_min_cluster_size = min_cluster_size.min(n_samples).max(2);
-    
-    // Create hyperparameters
-    let hyper_params = HdbscanHyperParams::builder()
-        .min_cluster_size(adjusted_min_cluster_size)
-        .min_samples(adjusted_min_samples)
-        .build();
-    
-    // Create HDBSCAN instance and run clustering
-    let clusterer = Hdbscan::new(&data_vec, hyper_params);
-    
-    // Run the clustering algorithm - cluster() returns Result<Vec<i32>, HdbscanError>
-    let labels = clusterer.cluster().map_err(|e| {
-        Error::new(
-            magnus::exception::runtime_error(),
-            format!("HDBSCAN clustering failed: {:?}", e)
-        )
-    })?;
-    
-    // Convert results to Ruby types
-    let ruby = magnus::Ruby::get().unwrap();
+    if adjusted_min_cluster_size != min_cluster_size {
+        adjusted_parameters.aset("min_cluster_size", (min_cluster_size, adjusted_min_cluster_size))?;
+    }
+
+    let hyper_params = HdbscanHyperParams {
+        min_samples: adjusted_min_samples,
+        min_cluster_size: adjusted_min_cluster_size,
+        cluster_selection_epsilon,
+        cluster_selection_method,
+        allow_single_cluster,
+        max_cluster_size,
+        alpha,
+    };
+
+    let tree = if use_hnsw {
+        hdbscan_hnsw::fit(&data_array, metric, &hyper_params, n_neighbors, random_seed)
+    } else {
+        hdbscan_tree::fit(&data_array, metric, &hyper_params)
+    };
+
     let result = RHash::new();
-    
-    // Convert labels (i32 to Ruby Integer, -1 for noise)
-    let labels_array = RArray::new();
-    for &label in labels.iter() {
-        labels_array.push(Integer::from_value(
-            ruby.eval(&format!("{}", label)).unwrap()
-        ).unwrap())?;
+    result.aset("adjusted_parameters", adjusted_parameters)?;
+
+    // Per-cluster values keyed by label
+    let persistence_hash = RHash::new();
+    let stability_hash = RHash::new();
+    for (label, (persistence, stability)) in tree.cluster_persistence().into_iter().zip(tree.cluster_stability()).enumerate() {
+        persistence_hash.aset(label, persistence)?;
+        stability_hash.aset(label, stability)?;
     }
-    result.aset("labels", labels_array)?;
-    
-    // For now, we'll create dummy probabilities and outlier scores
-    // since the basic hdbscan crate doesn't provide these
-    // In the future, we could calculate these ourselves or use a more advanced implementation
-    
-    // Create probabilities array (all 1.0 for clustered points, 0.0 for noise)
-    let probs_array = RArray::new();
-    for &label in labels.iter() {
-        let prob = if label == -1 { 0.0 } else { 1.0 };
-        probs_array.push(prob)?;
+    result.aset("cluster_persistence", persistence_hash)?;
+    result.aset("cluster_stability", stability_hash)?;
+
+    // Condensed tree rows: [parent, child, lambda_val, child_size]
+    let condensed_tree = RArray::with_capacity(tree.condensed.len());
+    for row in &tree.condensed {
+        condensed_tree.push((row.parent, row.child, row.lambda, row.size))?;
     }
-    result.aset("probabilities", probs_array)?;
-    
-    // Create outlier scores array (0.0 for clustered points, 1.0 for noise)
-    let outlier_array = RArray::new();
-    for &label in labels.iter() {
-        let score = if label == -1 { 1.0 } else { 0.0 };
-        outlier_array.push(score)?;
+    result.aset("condensed_tree", condensed_tree)?;
+
+    // Single-linkage tree in SciPy linkage format: [left, right, distance, size]
+    let single_linkage_tree = RArray::with_capacity(tree.linkage.len());
+    for row in &tree.linkage {
+        single_linkage_tree.push((row.left, row.right, row.distance, row.size))?;
     }
-    result.aset("outlier_scores", outlier_array)?;
-    
-    // Create empty cluster persistence hash for now
-    let persistence_hash = RHash::new();
-    result.aset("cluster_persistence", persistence_hash)?;
-    
+    result.aset("single_linkage_tree", single_linkage_tree)?;
+
+    result.aset("labels", RArray::from_vec(tree.labels.clone()))?;
+    result.aset("probabilities", RArray::from_vec(tree.probabilities.clone()))?;
+    result.aset("outlier_scores", RArray::from_vec(tree.outlier_scores.clone()))?;
+
+    // Cluster representatives; centroids only exist in a vector space
+    let centroids = match metric {
+        Metric::Precomputed => None,
+        _ => {
+            let centroids = tree.centroids(&data_array);
+            let centroids_array = RArray::with_capacity(centroids.nrows());
+            for centroid in centroids.rows() {
+                centroids_array.push(RArray::from_vec(centroid.to_vec()))?;
+            }
+            Some(centroids_array)
+        }
+    };
+    result.aset("centroids", centroids)?;
+    let max_candidates = if use_hnsw { hdbscan_hnsw::MEDOID_CANDIDATES } else { usize::MAX };
+    result.aset("medoid_indices", RArray::from_vec(tree.medoids(&metric.pairwise(&data_array), max_candidates)))?;
+
+    let exemplars = tree.exemplars();
+    let exemplar_indices = RArray::with_capacity(exemplars.len());
+    for points in &exemplars {
+        exemplar_indices.push(RArray::from_vec(points.clone()))?;
+    }
+    result.aset("exemplar_indices", exemplar_indices)?;
+
+    let data = match metric {
+        Metric::Precomputed => None,
+        _ => Some(data_array),
+    };
+    result.aset("model", HdbscanModel {
+        data,
+        metric,
+        params: hyper_params,
+        tree,
+        exemplars,
+    })?;
+
     Ok(result)
 }
 
@@ -86,8 +232,12 @@ pub fn hdbscan_fit(
 pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
     clustering_module.define_singleton_method(
         "hdbscan_rust",
-        function!(hdbscan_fit, 4),
+        function!(hdbscan_fit, 12),
     )?;
-    
+
+    let model_class = clustering_module.define_class("HdbscanModel", class::object())?;
+    model_class.define_method("approximate_predict", method!(HdbscanModel::approximate_predict, 1))?;
+    model_class.define_method("exemplars", method!(HdbscanModel::exemplars, 0))?;
+
     Ok(())
-}
\ No newline at end of file
+}
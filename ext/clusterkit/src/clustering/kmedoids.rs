@@ -0,0 +1,364 @@
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use ndarray::Array2;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use crate::errors::dimension_error;
+use crate::utils::ruby_array_to_ndarray;
+use super::normalize_rows;
+
+// CLARA runs PAM on this many random samples and keeps the best medoids
+const CLARA_SAMPLES: usize = 5;
+// Default CLARA sample size is 40 + 2k, as proposed by Kaufman & Rousseeuw
+const CLARA_BASE_SAMPLE_SIZE: usize = 40;
+
+/// Initialize k-medoids module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "kmedoids_rust",
+        function!(kmedoids, 7),
+    )?;
+
+    Ok(())
+}
+
+/// Dissimilarity between two samples of the input
+enum Dissimilarity {
+    Euclidean(Array2<f64>),
+    Manhattan(Array2<f64>),
+    /// Rows are unit-normalized up front so the distance is 1 - dot product
+    Cosine(Array2<f64>),
+    /// The input is already an n x n distance matrix
+    Precomputed(Array2<f64>),
+}
+
+impl Dissimilarity {
+    fn new(data: Array2<f64>, metric: &str) -> Result<Self, Error> {
+        match metric {
+            "euclidean" => Ok(Dissimilarity::Euclidean(data)),
+            "manhattan" => Ok(Dissimilarity::Manhattan(data)),
+            "cosine" => {
+                let mut data = data;
+                normalize_rows(&mut data);
+                Ok(Dissimilarity::Cosine(data))
+            }
+            "precomputed" => {
+                if data.nrows() != data.ncols() {
+                    return Err(dimension_error(format!(
+                        "Precomputed distance matrix must be square (got {} x {})",
+                        data.nrows(), data.ncols()
+                    )));
+                }
+                Ok(Dissimilarity::Precomputed(data))
+            }
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!(
+                    "Unknown k-medoids metric: {} (expected euclidean, manhattan, cosine or precomputed)",
+                    other
+                ),
+            )),
+        }
+    }
+
+    fn n_samples(&self) -> usize {
+        match self {
+            Dissimilarity::Euclidean(data)
+            | Dissimilarity::Manhattan(data)
+            | Dissimilarity::Cosine(data)
+            | Dissimilarity::Precomputed(data) => data.nrows(),
+        }
+    }
+
+    fn between(&self, i: usize, j: usize) -> f64 {
+        match self {
+            Dissimilarity::Euclidean(data) => data
+                .row(i)
+                .iter()
+                .zip(data.row(j).iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt(),
+            Dissimilarity::Manhattan(data) => data
+                .row(i)
+                .iter()
+                .zip(data.row(j).iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum(),
+            Dissimilarity::Cosine(data) => (1.0 - data.row(i).dot(&data.row(j))).max(0.0),
+            Dissimilarity::Precomputed(distances) => distances[[i, j]],
+        }
+    }
+}
+
+/// Nearest and second-nearest medoid of each point
+struct Assignment {
+    // Position in the medoid list, not a sample index
+    nearest: Vec<usize>,
+    d_nearest: Vec<f64>,
+    d_second: Vec<f64>,
+}
+
+impl Assignment {
+    fn cost(&self) -> f64 {
+        self.d_nearest.iter().sum()
+    }
+}
+
+/// Perform k-medoids clustering
+/// `algorithm` is "pam" (BUILD + FastPAM1 swaps on all points) or "clara"
+/// (PAM on random samples, keeping the medoids with the lowest total cost)
+/// Returns a hash with medoid_indices, labels, cost and n_iter
+fn kmedoids(
+    data: Value,
+    k: usize,
+    metric: String,
+    algorithm: String,
+    max_iter: usize,
+    random_seed: Option<i64>,
+    sample_size: Option<usize>,
+) -> Result<RHash, Error> {
+    let data_array = ruby_array_to_ndarray::<f64>(data)?;
+    let dissimilarity = Dissimilarity::new(data_array, &metric)?;
+    let n_samples = dissimilarity.n_samples();
+
+    if k == 0 || k > n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("k ({}) must be between 1 and the number of samples ({})", k, n_samples),
+        ));
+    }
+
+    let all_points: Vec<usize> = (0..n_samples).collect();
+    let (mut medoids, n_iter) = match algorithm.as_str() {
+        "pam" => pam(&dissimilarity, &all_points, k, max_iter),
+        "clara" => {
+            let sample_size = sample_size.unwrap_or(CLARA_BASE_SAMPLE_SIZE + 2 * k);
+            if sample_size < k {
+                return Err(Error::new(
+                    magnus::exception::arg_error(),
+                    format!("sample_size ({}) cannot be smaller than k ({})", sample_size, k),
+                ));
+            }
+            clara(&dissimilarity, k, max_iter, sample_size, random_seed)
+        }
+        other => {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown k-medoids algorithm: {} (expected pam or clara)", other),
+            ));
+        }
+    };
+
+    // Report medoids in index order so labels are stable across methods
+    medoids.sort_unstable();
+    let assignment = assign(&dissimilarity, &all_points, &medoids);
+
+    let result = RHash::new();
+    result.aset("medoid_indices", RArray::from_vec(medoids))?;
+    result.aset("labels", RArray::from_vec(assignment.nearest.clone()))?;
+    result.aset("cost", assignment.cost())?;
+    result.aset("n_iter", n_iter)?;
+
+    Ok(result)
+}
+
+/// PAM restricted to `points`: greedy BUILD, then FastPAM1 swaps
+/// Returns the medoids (sample indices) and the number of swaps performed
+fn pam(dissimilarity: &Dissimilarity, points: &[usize], k: usize, max_iter: usize) -> (Vec<usize>, usize) {
+    let mut medoids = build(dissimilarity, points, k);
+    let mut assignment = assign(dissimilarity, points, &medoids);
+    let mut n_iter = 0;
+
+    while n_iter < max_iter {
+        let Some((slot, candidate)) = best_swap(dissimilarity, points, &medoids, &assignment) else {
+            break;
+        };
+
+        // Accept the swap only if it really lowers the cost, so rounding in
+        // the swap deltas can never make the search cycle
+        let previous = medoids[slot];
+        medoids[slot] = candidate;
+        let swapped = assign(dissimilarity, points, &medoids);
+        if swapped.cost() >= assignment.cost() {
+            medoids[slot] = previous;
+            break;
+        }
+
+        assignment = swapped;
+        n_iter += 1;
+    }
+
+    (medoids, n_iter)
+}
+
+/// Greedy BUILD initialization: start from the most central point, then keep
+/// adding the point that lowers the total distance the most
+fn build(dissimilarity: &Dissimilarity, points: &[usize], k: usize) -> Vec<usize> {
+    let first = points
+        .par_iter()
+        .map(|&candidate| {
+            let total: f64 = points.iter().map(|&o| dissimilarity.between(o, candidate)).sum();
+            (total, candidate)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+        .map(|(_, candidate)| candidate)
+        .expect("points is not empty");
+
+    let mut medoids = vec![first];
+    let mut d_nearest: Vec<f64> = points.iter().map(|&o| dissimilarity.between(o, first)).collect();
+
+    while medoids.len() < k {
+        let next = points
+            .par_iter()
+            .filter(|&&candidate| !medoids.contains(&candidate))
+            .map(|&candidate| {
+                let gain: f64 = points
+                    .iter()
+                    .zip(&d_nearest)
+                    .map(|(&o, &d)| (d - dissimilarity.between(o, candidate)).max(0.0))
+                    .sum();
+                (gain, candidate)
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(_, candidate)| candidate)
+            .expect("k is at most the number of points");
+
+        for (d, &o) in d_nearest.iter_mut().zip(points) {
+            *d = d.min(dissimilarity.between(o, next));
+        }
+        medoids.push(next);
+    }
+
+    medoids
+}
+
+/// Find the swap that lowers the total distance the most, if any
+/// FastPAM1 evaluates all k medoids for a candidate in a single pass over the
+/// points, using each point's nearest and second-nearest medoid distances
+/// Returns (medoid position, candidate sample index)
+fn best_swap(
+    dissimilarity: &Dissimilarity,
+    points: &[usize],
+    medoids: &[usize],
+    assignment: &Assignment,
+) -> Option<(usize, usize)> {
+    let k = medoids.len();
+
+    let (delta, slot, candidate) = points
+        .par_iter()
+        .filter(|&&candidate| !medoids.contains(&candidate))
+        .map(|&candidate| {
+            // Change in cost when medoid i is replaced by the candidate; the
+            // shared term applies to every i and is added at the end
+            let mut delta = vec![0.0; k];
+            let mut shared = 0.0;
+            for (p, &o) in points.iter().enumerate() {
+                let d_candidate = dissimilarity.between(o, candidate);
+                let nearest = assignment.nearest[p];
+                let d_nearest = assignment.d_nearest[p];
+                if d_candidate < d_nearest {
+                    // The point moves to the candidate whichever medoid is removed
+                    shared += d_candidate - d_nearest;
+                } else {
+                    // Only removing its own medoid moves the point
+                    delta[nearest] += d_candidate.min(assignment.d_second[p]) - d_nearest;
+                }
+            }
+
+            delta
+                .iter()
+                .enumerate()
+                .map(|(slot, d)| (d + shared, slot, candidate))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .expect("k is at least 1")
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0).then(a.2.cmp(&b.2)).then(a.1.cmp(&b.1)))?;
+
+    (delta < 0.0).then_some((slot, candidate))
+}
+
+/// Nearest and second-nearest medoid for every point, in parallel
+fn assign(dissimilarity: &Dissimilarity, points: &[usize], medoids: &[usize]) -> Assignment {
+    let rows: Vec<(usize, f64, f64)> = points
+        .par_iter()
+        .map(|&o| {
+            let mut nearest = 0;
+            let mut d_nearest = f64::INFINITY;
+            let mut d_second = f64::INFINITY;
+            for (slot, &medoid) in medoids.iter().enumerate() {
+                let d = dissimilarity.between(o, medoid);
+                if d < d_nearest {
+                    d_second = d_nearest;
+                    d_nearest = d;
+                    nearest = slot;
+                } else if d < d_second {
+                    d_second = d;
+                }
+            }
+            (nearest, d_nearest, d_second)
+        })
+        .collect();
+
+    let mut assignment = Assignment {
+        nearest: Vec::with_capacity(rows.len()),
+        d_nearest: Vec::with_capacity(rows.len()),
+        d_second: Vec::with_capacity(rows.len()),
+    };
+    for (nearest, d_nearest, d_second) in rows {
+        assignment.nearest.push(nearest);
+        assignment.d_nearest.push(d_nearest);
+        assignment.d_second.push(d_second);
+    }
+    assignment
+}
+
+/// CLARA: run PAM on random samples and keep the medoids with the lowest
+/// total cost over all points. Later samples always include the best medoids
+/// found so far. Returns the medoids and the swaps performed in the best run
+fn clara(
+    dissimilarity: &Dissimilarity,
+    k: usize,
+    max_iter: usize,
+    sample_size: usize,
+    random_seed: Option<i64>,
+) -> (Vec<usize>, usize) {
+    let n_samples = dissimilarity.n_samples();
+    let all_points: Vec<usize> = (0..n_samples).collect();
+
+    if sample_size >= n_samples {
+        return pam(dissimilarity, &all_points, k, max_iter);
+    }
+
+    let mut rng: Box<dyn RngCore> = match random_seed {
+        Some(seed) => Box::new(StdRng::seed_from_u64(seed as u64)),
+        None => Box::new(thread_rng()),
+    };
+
+    let mut best: Option<(f64, Vec<usize>, usize)> = None;
+    for _ in 0..CLARA_SAMPLES {
+        let mut sample = rand::seq::index::sample(&mut rng, n_samples, sample_size).into_vec();
+        if let Some((_, medoids, _)) = &best {
+            for medoid in medoids {
+                if !sample.contains(medoid) {
+                    sample.push(*medoid);
+                }
+            }
+        }
+        sample.sort_unstable();
+
+        let (medoids, n_iter) = pam(dissimilarity, &sample, k, max_iter);
+        let cost = assign(dissimilarity, &all_points, &medoids).cost();
+        let improved = match &best {
+            Some((best_cost, _, _)) => cost < *best_cost,
+            None => true,
+        };
+        if improved {
+            best = Some((cost, medoids, n_iter));
+        }
+    }
+
+    let (_, medoids, n_iter) = best.expect("CLARA draws at least one sample");
+    (medoids, n_iter)
+}
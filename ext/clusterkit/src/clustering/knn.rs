@@ -0,0 +1,97 @@
+use hnsw_rs::prelude::*;
+use ndarray::Array2;
+use rayon::prelude::*;
+use crate::hnsw::build_l2_index;
+use super::metric::Metric;
+
+/// HNSW graph settings, matching the ClusterKit::HNSW defaults
+const MAX_NB_CONNECTION: usize = 16;
+const EF_CONSTRUCTION: usize = 200;
+
+/// The `k` nearest neighbors of every point (the point itself first) with
+/// their exact distances, in increasing order. A precomputed distance matrix
+/// is ranked row by row; otherwise candidates come from an HNSW index
+pub(super) fn knn_graph<F>(
+    data: &Array2<f64>,
+    metric: Metric,
+    k: usize,
+    random_seed: Option<u64>,
+    distance: &F,
+) -> Vec<Vec<(usize, f64)>>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    match metric {
+        Metric::Precomputed => (0..data.nrows())
+            .into_par_iter()
+            .map(|i| neighbor_list(i, 0..data.nrows(), k, distance))
+            .collect(),
+        _ => KnnIndex::build(data, random_seed).knn_graph(metric, k, distance),
+    }
+}
+
+/// The `k` nearest of the candidates of point `i` with their distances, in
+/// increasing order; the point itself comes first, and ties go to the lowest index
+fn neighbor_list<F>(i: usize, candidates: impl IntoIterator<Item = usize>, k: usize, distance: &F) -> Vec<(usize, f64)>
+where
+    F: Fn(usize, usize) -> f64,
+{
+    let mut list: Vec<(usize, f64)> = candidates
+        .into_iter()
+        .filter(|&j| j != i)
+        .map(|j| (j, distance(i, j)))
+        .collect();
+    list.push((i, 0.0));
+    list.sort_by(|a, b| a.1.total_cmp(&b.1).then((a.0 != i).cmp(&(b.0 != i))).then(a.0.cmp(&b.0)));
+    list.truncate(k);
+    list
+}
+
+/// An L2 HNSW index over the rows of the data
+pub(super) struct KnnIndex {
+    hnsw: Hnsw<'static, f32, DistL2>,
+    pub(super) points: Vec<Vec<f32>>,
+}
+
+impl KnnIndex {
+    pub(super) fn build(data: &Array2<f64>, random_seed: Option<u64>) -> Self {
+        let points: Vec<Vec<f32>> = data
+            .rows()
+            .into_iter()
+            .map(|row| row.iter().map(|&x| x as f32).collect())
+            .collect();
+        let hnsw = build_l2_index(&points, MAX_NB_CONNECTION, EF_CONSTRUCTION, random_seed);
+        KnnIndex { hnsw, points }
+    }
+
+    /// Ids of the approximate `k` nearest points to each query, by L2 distance
+    pub(super) fn search(&self, queries: &[Vec<f32>], k: usize) -> Vec<Vec<usize>> {
+        let ef_search = (2 * k).max(64);
+        self.hnsw
+            .parallel_search(queries, k, ef_search)
+            .into_iter()
+            .map(|found| found.iter().map(|neighbor| neighbor.d_id).collect())
+            .collect()
+    }
+
+    /// The `k` nearest neighbors of every point, see `knn_graph`
+    /// Candidates come from the L2 index, whose order is exact for euclidean
+    /// and for cosine on unit rows; for other metrics twice as many candidates
+    /// are re-ranked by the metric
+    pub(super) fn knn_graph<F>(&self, metric: Metric, k: usize, distance: &F) -> Vec<Vec<(usize, f64)>>
+    where
+        F: Fn(usize, usize) -> f64 + Sync,
+    {
+        let n_samples = self.points.len();
+        let candidates = match metric {
+            Metric::Euclidean | Metric::Cosine => k,
+            _ => (2 * k).min(n_samples),
+        };
+
+        self.search(&self.points, candidates)
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, found)| neighbor_list(i, found, k, distance))
+            .collect()
+    }
+}
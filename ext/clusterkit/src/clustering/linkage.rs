@@ -0,0 +1,85 @@
+use rayon::prelude::*;
+
+/// Merge in the single-linkage tree, in SciPy linkage format: nodes below
+/// n_samples are points and node n_samples + i is the merge on row i
+#[derive(Clone, Copy)]
+pub(super) struct LinkageRow {
+    pub(super) left: usize,
+    pub(super) right: usize,
+    pub(super) distance: f64,
+    pub(super) size: usize,
+}
+
+/// Minimum spanning tree of the complete graph over n_samples points weighted
+/// by `distance`, built with Prim's algorithm in O(n²) time and O(n) memory.
+/// Ties go to the lowest index
+pub(super) fn minimum_spanning_tree<F>(n_samples: usize, distance: &F) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    let mut in_tree = vec![false; n_samples];
+    let mut best = vec![f64::INFINITY; n_samples];
+    let mut from = vec![0usize; n_samples];
+    let mut edges = Vec::with_capacity(n_samples.saturating_sub(1));
+
+    let mut current = 0;
+    in_tree[0] = true;
+    for _ in 1..n_samples {
+        // Relax the edges from the point just added, then take the closest point outside the tree
+        let (next, weight) = best
+            .par_iter_mut()
+            .zip(from.par_iter_mut())
+            .enumerate()
+            .filter(|(j, _)| !in_tree[*j])
+            .map(|(j, (best_j, from_j))| {
+                let weight = distance(current, j);
+                if weight < *best_j {
+                    *best_j = weight;
+                    *from_j = current;
+                }
+                (j, *best_j)
+            })
+            .reduce(
+                || (usize::MAX, f64::INFINITY),
+                |a, b| if b.1 < a.1 || (b.1 == a.1 && b.0 < a.0) { b } else { a },
+            );
+
+        in_tree[next] = true;
+        edges.push((from[next], next, weight));
+        current = next;
+    }
+
+    edges
+}
+
+/// Linkage rows from merges given as (a point of one cluster, a point of the
+/// other, distance), such as MST edges, in order of increasing distance; the
+/// sort is stable so merges at equal distances keep their order
+pub(super) fn linkage_from_merges(n_samples: usize, mut merges: Vec<(usize, usize, f64)>) -> Vec<LinkageRow> {
+    merges.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    // Union-find over points; each root remembers the linkage node it currently is
+    let mut parent: Vec<usize> = (0..n_samples).collect();
+    let mut node: Vec<usize> = (0..n_samples).collect();
+    let mut size = vec![1usize; n_samples];
+
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    merges.into_iter()
+        .enumerate()
+        .map(|(row, (a, b, distance))| {
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            let (left, right) = (node[root_a].min(node[root_b]), node[root_a].max(node[root_b]));
+            parent[root_b] = root_a;
+            size[root_a] += size[root_b];
+            node[root_a] = n_samples + row;
+            LinkageRow { left, right, distance, size: size[root_a] }
+        })
+        .collect()
+}
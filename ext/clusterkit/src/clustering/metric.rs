@@ -0,0 +1,71 @@
+use magnus::Error;
+use ndarray::{Array2, ArrayView1};
+use crate::errors::dimension_error;
+use super::{normalize_rows, squared_euclidean_distance};
+
+/// Distance between samples, for the clustering algorithms that take a metric name
+#[derive(Clone, Copy)]
+pub(super) enum Metric {
+    Euclidean,
+    Manhattan,
+    /// 1 - cosine similarity, on rows normalized up front
+    Cosine,
+    /// Largest coordinate difference
+    Chebyshev,
+    /// The input is already an n x n distance matrix
+    Precomputed,
+}
+
+impl Metric {
+    pub(super) fn parse(metric: &str) -> Result<Self, Error> {
+        match metric {
+            "euclidean" | "l2" => Ok(Metric::Euclidean),
+            "manhattan" | "l1" => Ok(Metric::Manhattan),
+            "cosine" => Ok(Metric::Cosine),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "precomputed" => Ok(Metric::Precomputed),
+            other => Err(Error::new(
+                magnus::exception::arg_error(),
+                format!(
+                    "Unknown metric: {} (expected euclidean, manhattan, cosine, chebyshev or precomputed)",
+                    other
+                ),
+            )),
+        }
+    }
+
+    /// Bring data into the form `distance` expects: unit rows for cosine,
+    /// a square matrix for precomputed distances
+    pub(super) fn prepare(self, mut data: Array2<f64>) -> Result<Array2<f64>, Error> {
+        match self {
+            Metric::Cosine => normalize_rows(&mut data),
+            Metric::Precomputed if data.nrows() != data.ncols() => {
+                return Err(dimension_error(format!(
+                    "Precomputed distance matrix must be square (got {} x {})",
+                    data.nrows(), data.ncols()
+                )));
+            }
+            _ => {}
+        }
+        Ok(data)
+    }
+
+    /// Distance between two prepared points; not defined for precomputed distances
+    pub(super) fn distance(self, a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
+        match self {
+            Metric::Euclidean => squared_euclidean_distance(a, b).sqrt(),
+            Metric::Manhattan => a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum(),
+            Metric::Cosine => (1.0 - a.dot(b)).max(0.0),
+            Metric::Chebyshev => a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max),
+            Metric::Precomputed => unreachable!("precomputed distances are looked up, not computed"),
+        }
+    }
+
+    /// Distance between rows i and j of prepared data
+    pub(super) fn pairwise(self, data: &Array2<f64>) -> impl Fn(usize, usize) -> f64 + Sync + '_ {
+        move |i, j| match self {
+            Metric::Precomputed => data[[i, j]],
+            metric => metric.distance(&data.row(i), &data.row(j)),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use ndarray::{Array2, Axis};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::errors::dimension_mismatch_error;
+use crate::utils::ruby_array_to_ndarray;
+use super::{
+    accumulate_clusters, assign_labels, kmeans_plusplus, relocate_clusters,
+    squared_euclidean_distance, KMeansMetric,
+};
+
+/// Initialize mini-batch k-means module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "minibatch_kmeans_rust",
+        function!(minibatch_kmeans, 6),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "minibatch_partial_fit_rust",
+        function!(minibatch_partial_fit, 5),
+    )?;
+
+    Ok(())
+}
+
+/// Fit mini-batch k-means on an in-memory dataset
+/// Runs steps on `batch_size` points sampled without replacement until the
+/// total squared centroid shift of a step drops to `tol` times the mean
+/// feature variance, or for `max_iter` steps
+/// Returns a hash with labels, centroids, counts, inertia, n_iter, converged
+/// and n_relocations
+fn minibatch_kmeans(
+    data: Value,
+    k: usize,
+    batch_size: usize,
+    max_iter: usize,
+    tol: f64,
+    random_seed: Option<i64>,
+) -> Result<RHash, Error> {
+    let data_array = ruby_array_to_ndarray::<f64>(data)?;
+    let n_samples = data_array.nrows();
+
+    if k > n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("k ({}) cannot be larger than number of samples ({})", k, n_samples),
+        ));
+    }
+
+    if batch_size == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "batch_size must be at least 1",
+        ));
+    }
+
+    if tol.is_nan() || tol < 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("tol must be non-negative (got {})", tol),
+        ));
+    }
+
+    let mut rng: Box<dyn RngCore> = match random_seed {
+        Some(seed) => Box::new(StdRng::seed_from_u64(seed as u64)),
+        None => Box::new(thread_rng()),
+    };
+
+    // Seed the centroids with k-means++ on an initial sample (at least k points)
+    let init_size = batch_size.max(k).min(n_samples);
+    let init_indices = rand::seq::index::sample(&mut rng, n_samples, init_size).into_vec();
+    let init_sample = data_array.select(Axis(0), &init_indices);
+    let init_seed = random_seed.map(|_| rng.gen::<i64>());
+    let mut centroids = kmeans_plusplus(&init_sample, None, KMeansMetric::Euclidean, k, init_seed);
+    let mut counts = vec![0.0f64; k];
+
+    // tol is relative to the data: scale it by the mean per-feature variance
+    let tol = tol * data_array.var_axis(Axis(0), 0.0).mean().unwrap_or(0.0);
+    let step_size = batch_size.min(n_samples);
+    let mut n_iter = 0;
+    let mut converged = false;
+    let mut n_relocations = 0;
+    while n_iter < max_iter {
+        let indices = rand::seq::index::sample(&mut rng, n_samples, step_size).into_vec();
+        let batch = data_array.select(Axis(0), &indices);
+        let (shift, relocated) = minibatch_step(&batch, &mut centroids, &mut counts);
+        n_iter += 1;
+        n_relocations += relocated;
+
+        // A step that relocated a centroid is never the last one
+        if relocated == 0 && shift <= tol {
+            converged = true;
+            break;
+        }
+    }
+
+    // Final labels and inertia over the full dataset
+    let assignments = assign_labels(&data_array, &centroids, KMeansMetric::Euclidean);
+    let inertia: f64 = assignments.iter().map(|&(_, dist)| dist).sum();
+
+    let result = RHash::new();
+    let labels_array = RArray::with_capacity(n_samples);
+    for (label, _) in assignments {
+        labels_array.push(label)?;
+    }
+    result.aset("labels", labels_array)?;
+    result.aset("centroids", matrix_to_ruby(&centroids)?)?;
+    result.aset("counts", RArray::from_vec(counts))?;
+    result.aset("inertia", inertia)?;
+    result.aset("n_iter", n_iter)?;
+    result.aset("converged", converged)?;
+    result.aset("n_relocations", n_relocations)?;
+
+    Ok(result)
+}
+
+/// Update centroids with one batch of data
+/// `centroids` and `counts` are nil on the first call, in which case the
+/// centroids are initialized with k-means++ on the batch
+/// Returns a hash with the updated centroids and counts
+fn minibatch_partial_fit(
+    batch: Value,
+    centroids: Option<Value>,
+    counts: Option<Vec<f64>>,
+    k: usize,
+    random_seed: Option<i64>,
+) -> Result<RHash, Error> {
+    let batch_array = ruby_array_to_ndarray::<f64>(batch)?;
+    let n_features = batch_array.ncols();
+
+    let (mut centroids, mut counts) = match centroids {
+        Some(centroids) => {
+            let centroids = ruby_array_to_ndarray::<f64>(centroids)?;
+            if centroids.ncols() != n_features {
+                return Err(dimension_mismatch_error(format!(
+                    "Batch has {} features but centroids have {}", n_features, centroids.ncols()
+                )));
+            }
+            let counts = counts.unwrap_or_else(|| vec![0.0; centroids.nrows()]);
+            if counts.len() != centroids.nrows() {
+                return Err(Error::new(
+                    magnus::exception::arg_error(),
+                    format!("counts has {} entries but there are {} centroids", counts.len(), centroids.nrows()),
+                ));
+            }
+            (centroids, counts)
+        }
+        None => {
+            if k > batch_array.nrows() {
+                return Err(Error::new(
+                    magnus::exception::arg_error(),
+                    format!(
+                        "The first batch must contain at least k ({}) samples (got {})",
+                        k, batch_array.nrows()
+                    ),
+                ));
+            }
+            (kmeans_plusplus(&batch_array, None, KMeansMetric::Euclidean, k, random_seed), vec![0.0; k])
+        }
+    };
+
+    minibatch_step(&batch_array, &mut centroids, &mut counts);
+
+    let result = RHash::new();
+    result.aset("centroids", matrix_to_ruby(&centroids)?)?;
+    result.aset("counts", RArray::from_vec(counts))?;
+
+    Ok(result)
+}
+
+/// Move each centroid towards the mean of its batch points
+/// Each centroid has its own learning rate, batch_count / total_count, so a
+/// centroid is always the running mean of every point ever assigned to it.
+/// A centroid that has never been assigned a point is dead: it takes the batch
+/// point farthest from its own centroid, as empty clusters do in full k-means
+/// Returns the total squared centroid shift and the number of centroids relocated
+fn minibatch_step(
+    batch: &Array2<f64>,
+    centroids: &mut Array2<f64>,
+    counts: &mut [f64],
+) -> (f64, usize) {
+    let k = centroids.nrows();
+    let mut labels: Vec<usize> = assign_labels(batch, centroids, KMeansMetric::Euclidean)
+        .into_iter()
+        .map(|(label, _)| label)
+        .collect();
+    let (mut sums, mut batch_counts) = accumulate_clusters(batch, None, &labels, k);
+
+    // Clusters that only missed this batch keep their centroid
+    let dead: Vec<usize> = (0..k)
+        .filter(|&j| counts[j] == 0.0 && batch_counts[j] == 0.0)
+        .collect();
+    let relocated = relocate_clusters(
+        dead,
+        batch,
+        None,
+        centroids,
+        KMeansMetric::Euclidean,
+        &mut labels,
+        &mut sums,
+        &mut batch_counts,
+    );
+
+    let mut shift = 0.0;
+    for j in 0..k {
+        if batch_counts[j] == 0.0 {
+            continue;
+        }
+
+        counts[j] += batch_counts[j];
+        let learning_rate = batch_counts[j] / counts[j];
+        let batch_mean = &sums.row(j) / batch_counts[j];
+        let old = centroids.row(j).to_owned();
+        let mut centroid = centroids.row_mut(j);
+        centroid *= 1.0 - learning_rate;
+        centroid.scaled_add(learning_rate, &batch_mean);
+        shift += squared_euclidean_distance(&old.view(), &centroids.row(j));
+    }
+
+    (shift, relocated)
+}
+
+fn matrix_to_ruby(matrix: &Array2<f64>) -> Result<RArray, Error> {
+    let rows = RArray::with_capacity(matrix.nrows());
+    for row in matrix.rows() {
+        rows.push(RArray::from_vec(row.to_vec()))?;
+    }
+    Ok(rows)
+}
@@ -0,0 +1,190 @@
+use magnus::{function, prelude::*, Error, RArray, RHash, Value};
+use ndarray::{Array2, Axis};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use crate::utils::ruby_array_to_ndarray;
+use super::validity::{silhouette, LabeledData};
+use super::{kmeans_best_of, EmptyClusterPolicy, KMeansAlgorithm, KMeansMetric, KMeansParams};
+
+/// Relative convergence tolerance, matching the KMeans default
+const TOL: f64 = 1e-4;
+
+/// Initialize k selection functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "kmeans_evaluate_k_rust",
+        function!(kmeans_evaluate_k, 7),
+    )?;
+
+    Ok(())
+}
+
+/// Euclidean k-means parameters for `k` clusters on `data`
+fn params_for(data: &Array2<f64>, k: usize, max_iter: usize) -> KMeansParams {
+    KMeansParams {
+        k,
+        max_iter,
+        tol: TOL * data.var_axis(Axis(0), 0.0).mean().unwrap_or(0.0),
+        algorithm: KMeansAlgorithm::Lloyd,
+        empty_cluster: EmptyClusterPolicy::Relocate,
+        metric: KMeansMetric::Euclidean,
+    }
+}
+
+/// Evaluate Euclidean k-means for every k in `ks`, in parallel
+/// Always reports the inertia per k. With `n_references` > 0 it also computes
+/// the gap statistic of Tibshirani, Walther and Hastie (2001) against that
+/// many uniform reference datasets drawn from the bounding box of the data,
+/// and with `compute_silhouette` the mean silhouette of each clustering
+/// `ks` is evaluated in increasing order with duplicates removed
+/// Returns a hash with k, inertia, gap, gap_sd and silhouette (nil when not
+/// computed), plus the k chosen by the gap and silhouette criteria
+fn kmeans_evaluate_k(
+    data: Value,
+    mut ks: Vec<usize>,
+    max_iter: usize,
+    random_seed: Option<i64>,
+    n_init: usize,
+    n_references: usize,
+    compute_silhouette: bool,
+) -> Result<RHash, Error> {
+    let data_array = ruby_array_to_ndarray::<f64>(data)?;
+    let n_samples = data_array.nrows();
+    ks.sort_unstable();
+    ks.dedup();
+
+    if ks.is_empty() {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "k range must not be empty",
+        ));
+    }
+
+    if let Some(&k) = ks.iter().find(|&&k| k == 0 || k > n_samples) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("k ({}) must be between 1 and the number of samples ({})", k, n_samples),
+        ));
+    }
+
+    if n_init == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "n_init must be at least 1",
+        ));
+    }
+
+    // Every k uses the same seed, so each result matches a KMeans fit with that seed
+    let runs: Vec<_> = ks
+        .par_iter()
+        .map(|&k| {
+            let params = params_for(&data_array, k, max_iter);
+            kmeans_best_of(&data_array, None, &params, random_seed, n_init)
+        })
+        .collect();
+    let inertia: Vec<f64> = runs.iter().map(|run| run.inertia).collect();
+
+    let silhouettes = compute_silhouette.then(|| {
+        runs.par_iter()
+            .map(|run| {
+                let labels = run.labels.iter().map(|&label| label as i64).collect();
+                silhouette(&LabeledData::renumbered(data_array.view(), labels))
+            })
+            .collect::<Vec<f64>>()
+    });
+
+    let gap = (n_references > 0).then(|| {
+        gap_statistic(&data_array, &ks, &inertia, max_iter, random_seed, n_init, n_references)
+    });
+
+    let gap_k = gap.as_ref().map(|(gap, gap_sd)| ks[gap_choice(gap, gap_sd)]);
+    let silhouette_k = silhouettes.as_ref().map(|scores| {
+        // Highest mean silhouette; ties go to the smallest k
+        let best = (0..scores.len())
+            .reduce(|best, i| if scores[i] > scores[best] { i } else { best })
+            .expect("k range is not empty");
+        ks[best]
+    });
+    let (gap, gap_sd) = gap.unzip();
+
+    let result = RHash::new();
+    result.aset("k", RArray::from_vec(ks))?;
+    result.aset("inertia", RArray::from_vec(inertia))?;
+    result.aset("gap", gap.map(RArray::from_vec))?;
+    result.aset("gap_sd", gap_sd.map(RArray::from_vec))?;
+    result.aset("gap_k", gap_k)?;
+    result.aset("silhouette", silhouettes.map(RArray::from_vec))?;
+    result.aset("silhouette_k", silhouette_k)?;
+
+    Ok(result)
+}
+
+/// Gap(k) = E*[log W*_k] - log W_k, where W_k is the inertia and the
+/// expectation is the mean over uniform reference datasets
+/// Returns the gap and its simulation error s_k = sd_k * sqrt(1 + 1/B) per k
+fn gap_statistic(
+    data: &Array2<f64>,
+    ks: &[usize],
+    inertia: &[f64],
+    max_iter: usize,
+    random_seed: Option<i64>,
+    n_init: usize,
+    n_references: usize,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut rng: Box<dyn RngCore> = match random_seed {
+        Some(seed) => Box::new(StdRng::seed_from_u64(seed as u64)),
+        None => Box::new(thread_rng()),
+    };
+
+    // The same reference datasets are clustered for every k
+    let mins = data.fold_axis(Axis(0), f64::INFINITY, |&a, &b| a.min(b));
+    let maxs = data.fold_axis(Axis(0), f64::NEG_INFINITY, |&a, &b| a.max(b));
+    let references: Vec<(Array2<f64>, i64)> = (0..n_references)
+        .map(|_| {
+            let reference = Array2::from_shape_fn(data.dim(), |(_, j)| {
+                if maxs[j] > mins[j] { rng.gen_range(mins[j]..maxs[j]) } else { mins[j] }
+            });
+            (reference, rng.gen::<i64>())
+        })
+        .collect();
+
+    // log W*_kb for every (k, reference) pair
+    let log_inertia: Vec<f64> = (0..ks.len() * n_references)
+        .into_par_iter()
+        .map(|pair| {
+            let (reference, seed) = &references[pair % n_references];
+            let params = params_for(reference, ks[pair / n_references], max_iter);
+            log_clamped(kmeans_best_of(reference, None, &params, Some(*seed), n_init).inertia)
+        })
+        .collect();
+
+    let b = n_references as f64;
+    (0..ks.len())
+        .map(|i| {
+            let logs = &log_inertia[i * n_references..(i + 1) * n_references];
+            let mean = logs.iter().sum::<f64>() / b;
+            let sd = (logs.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / b).sqrt();
+            (mean - log_clamped(inertia[i]), sd * (1.0 + 1.0 / b).sqrt())
+        })
+        .unzip()
+}
+
+/// Natural log that maps a zero inertia (every point on its centroid) to a
+/// large finite value instead of -inf
+fn log_clamped(inertia: f64) -> f64 {
+    inertia.max(f64::MIN_POSITIVE).ln()
+}
+
+/// Smallest k with Gap(k) >= Gap(k+1) - s_{k+1}, comparing each k with the
+/// next one evaluated; falls back to the largest gap if no k qualifies
+fn gap_choice(gap: &[f64], gap_sd: &[f64]) -> usize {
+    (0..gap.len().saturating_sub(1))
+        .find(|&i| gap[i] >= gap[i + 1] - gap_sd[i + 1])
+        .unwrap_or_else(|| {
+            (0..gap.len())
+                .reduce(|best, i| if gap[i] > gap[best] { i } else { best })
+                .expect("k range is not empty")
+        })
+}
@@ -0,0 +1,203 @@
+use std::collections::BinaryHeap;
+use ndarray::Array2;
+use super::metric::Metric;
+
+/// Points per KD-tree leaf, below which scanning beats splitting
+const LEAF_SIZE: usize = 16;
+
+/// Exact neighborhood queries over the rows of prepared data (see
+/// `Metric::prepare`): a KD-tree for vector metrics, row scans for a
+/// precomputed distance matrix
+pub(super) struct NeighborIndex<'a> {
+    data: &'a Array2<f64>,
+    metric: Metric,
+    /// Point indices, permuted so every node covers a contiguous range
+    order: Vec<usize>,
+    nodes: Vec<KdNode>,
+}
+
+/// Node covering `order[start..end]`, with the bounding box of its points
+struct KdNode {
+    start: usize,
+    end: usize,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    children: Option<(usize, usize)>,
+}
+
+/// Point at a distance, ordered by distance then index
+#[derive(PartialEq)]
+pub(super) struct Candidate(pub(super) f64, pub(super) usize);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+impl<'a> NeighborIndex<'a> {
+    pub(super) fn new(data: &'a Array2<f64>, metric: Metric) -> Self {
+        let n_samples = data.nrows();
+        let mut index = NeighborIndex {
+            data,
+            metric,
+            order: (0..n_samples).collect(),
+            nodes: Vec::new(),
+        };
+        if !matches!(metric, Metric::Precomputed) && n_samples > 0 {
+            index.build(0, n_samples);
+        }
+        index
+    }
+
+    /// Build the subtree over `order[start..end]`, returning its node id
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let n_features = self.data.ncols();
+        let mut lower = vec![f64::INFINITY; n_features];
+        let mut upper = vec![f64::NEG_INFINITY; n_features];
+        for &point in &self.order[start..end] {
+            for (feature, &value) in self.data.row(point).iter().enumerate() {
+                lower[feature] = lower[feature].min(value);
+                upper[feature] = upper[feature].max(value);
+            }
+        }
+
+        // Split the widest feature at the median
+        let split = (0..n_features)
+            .map(|feature| (feature, upper[feature] - lower[feature]))
+            .reduce(|best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+        let id = self.nodes.len();
+        self.nodes.push(KdNode { start, end, lower, upper, children: None });
+
+        if let Some((feature, width)) = split {
+            if end - start > LEAF_SIZE && width > 0.0 {
+                let middle = start + (end - start) / 2;
+                let data = self.data;
+                self.order[start..end].select_nth_unstable_by(middle - start, |&a, &b| {
+                    data[[a, feature]].total_cmp(&data[[b, feature]]).then(a.cmp(&b))
+                });
+                let left = self.build(start, middle);
+                let right = self.build(middle, end);
+                self.nodes[id].children = Some((left, right));
+            }
+        }
+        id
+    }
+
+    /// Lower bound on the distance from `point` to anything in the node's box
+    fn box_distance(&self, point: usize, node: &KdNode) -> f64 {
+        let gaps = self.data.row(point).into_iter().zip(node.lower.iter().zip(&node.upper)).map(|(&x, (&lo, &hi))| {
+            (lo - x).max(x - hi).max(0.0)
+        });
+        match self.metric {
+            Metric::Euclidean => gaps.map(|g| g * g).sum::<f64>().sqrt(),
+            Metric::Manhattan => gaps.sum(),
+            Metric::Chebyshev => gaps.fold(0.0, f64::max),
+            // For unit (or zero) rows 1 - a·b >= |a - b|² / 2
+            Metric::Cosine => gaps.map(|g| g * g).sum::<f64>() / 2.0,
+            Metric::Precomputed => unreachable!("precomputed distances have no tree"),
+        }
+    }
+
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        match self.metric {
+            Metric::Precomputed => self.data[[i, j]],
+            metric => metric.distance(&self.data.row(i), &self.data.row(j)),
+        }
+    }
+
+    /// Points within `radius` of training point `point` (itself included) with
+    /// their distances, in increasing index order
+    pub(super) fn radius_neighbors(&self, point: usize, radius: f64) -> Vec<(usize, f64)> {
+        let mut found = Vec::new();
+        if matches!(self.metric, Metric::Precomputed) {
+            for other in 0..self.data.nrows() {
+                let distance = self.distance(point, other);
+                if distance <= radius {
+                    found.push((other, distance));
+                }
+            }
+            return found;
+        }
+
+        let mut stack = vec![0];
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            if self.box_distance(point, node) > radius {
+                continue;
+            }
+            match node.children {
+                Some((left, right)) => stack.extend([right, left]),
+                None => {
+                    for &other in &self.order[node.start..node.end] {
+                        let distance = self.distance(point, other);
+                        if distance <= radius {
+                            found.push((other, distance));
+                        }
+                    }
+                }
+            }
+        }
+        found.sort_unstable_by_key(|&(other, _)| other);
+        found
+    }
+
+    /// The `k` points closest to training point `point` (itself included) with
+    /// their distances, in increasing distance order; ties go to the lowest index
+    pub(super) fn k_nearest(&self, point: usize, k: usize) -> Vec<(usize, f64)> {
+        let mut heap: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+        let offer = |heap: &mut BinaryHeap<Candidate>, other: usize, distance: f64| {
+            let candidate = Candidate(distance, other);
+            if heap.len() < k {
+                heap.push(candidate);
+            } else if heap.peek().is_some_and(|worst| candidate < *worst) {
+                heap.pop();
+                heap.push(candidate);
+            }
+        };
+
+        if matches!(self.metric, Metric::Precomputed) {
+            for other in 0..self.data.nrows() {
+                offer(&mut heap, other, self.distance(point, other));
+            }
+        } else if k > 0 {
+            let mut stack = vec![0];
+            while let Some(id) = stack.pop() {
+                let node = &self.nodes[id];
+                let bound = self.box_distance(point, node);
+                if heap.len() == k && heap.peek().is_some_and(|worst| bound > worst.0) {
+                    continue;
+                }
+                match node.children {
+                    Some((left, right)) => {
+                        // Visit the nearer child first so the heap tightens early
+                        let (near, far) = if self.box_distance(point, &self.nodes[left])
+                            <= self.box_distance(point, &self.nodes[right])
+                        {
+                            (left, right)
+                        } else {
+                            (right, left)
+                        };
+                        stack.extend([far, near]);
+                    }
+                    None => {
+                        for &other in &self.order[node.start..node.end] {
+                            offer(&mut heap, other, self.distance(point, other));
+                        }
+                    }
+                }
+            }
+        }
+
+        heap.into_sorted_vec().into_iter().map(|Candidate(distance, other)| (other, distance)).collect()
+    }
+}
@@ -0,0 +1,427 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use rayon::prelude::*;
+use crate::utils::ruby_array_to_ndarray;
+use super::metric::Metric;
+use super::neighbors::{Candidate, NeighborIndex};
+
+/// Initialize OPTICS module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "optics_rust",
+        function!(optics, 9),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "optics_extract_dbscan_rust",
+        function!(optics_extract_dbscan, 4),
+    )?;
+
+    Ok(())
+}
+
+/// Cluster extraction from the reachability plot
+enum ExtractionMethod {
+    /// Steep areas of the plot (Ankerst et al., as in scikit-learn)
+    Xi,
+    /// A horizontal cut at `eps`, equivalent to DBSCAN with that radius
+    Dbscan,
+}
+
+/// Perform OPTICS clustering
+/// Orders points so that dense regions are contiguous, recording for each the
+/// reachability distance from the points before it; neighborhoods are limited
+/// to `max_eps` (which may be infinite). `metric` is "euclidean" (or "l2"),
+/// "manhattan" (or "l1"), "cosine", "chebyshev" or "precomputed"
+/// Labels come from `cluster_method` "xi" (steepness `xi`, clusters of at least
+/// `min_cluster_size` points, nil meaning `min_samples`) or "dbscan" (a cut at
+/// `eps`, nil meaning `max_eps`)
+/// Returns a hash with labels (-1 for noise), core sample indices (points with
+/// a core distance within max_eps), the ordering, reachability and core
+/// distances and predecessors indexed by point, and for xi the cluster
+/// hierarchy as [start, end] positions in the ordering
+#[allow(clippy::too_many_arguments)]
+pub fn optics(
+    data: Value,
+    min_samples: usize,
+    max_eps: f64,
+    metric: String,
+    cluster_method: String,
+    eps: Option<f64>,
+    xi: f64,
+    min_cluster_size: Option<usize>,
+    predecessor_correction: bool,
+) -> Result<RHash, Error> {
+    let metric = Metric::parse(&metric)?;
+    let cluster_method = match cluster_method.as_str() {
+        "xi" => ExtractionMethod::Xi,
+        "dbscan" => ExtractionMethod::Dbscan,
+        other => {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("Unknown OPTICS cluster method: {} (expected xi or dbscan)", other),
+            ));
+        }
+    };
+
+    if min_samples == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "min_samples must be positive",
+        ));
+    }
+
+    if max_eps.is_nan() || max_eps <= 0.0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("max_eps must be positive (got {})", max_eps),
+        ));
+    }
+
+    if !(xi > 0.0 && xi < 1.0) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("xi must be between 0 and 1 (got {})", xi),
+        ));
+    }
+
+    let eps = eps.unwrap_or(max_eps);
+    if matches!(cluster_method, ExtractionMethod::Dbscan) {
+        validate_eps(eps, max_eps)?;
+    }
+
+    let data_array = metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?;
+    let index = NeighborIndex::new(&data_array, metric);
+    let n_samples = data_array.nrows();
+
+    // Core distance: distance to the min_samples-th neighbor (the point itself
+    // being the first), infinite when that is beyond max_eps
+    let core_distances: Vec<f64> = (0..n_samples)
+        .into_par_iter()
+        .map(|point| match index.k_nearest(point, min_samples).get(min_samples - 1) {
+            Some(&(_, distance)) if distance <= max_eps => distance,
+            _ => f64::INFINITY,
+        })
+        .collect();
+
+    let (ordering, reachability, predecessor) = compute_ordering(&index, &core_distances, max_eps);
+
+    let (labels, cluster_hierarchy) = match cluster_method {
+        ExtractionMethod::Xi => {
+            let min_cluster_size = min_cluster_size.unwrap_or(min_samples).max(2);
+            let reachability_plot: Vec<f64> = ordering.iter().map(|&point| reachability[point]).collect();
+            let predecessor_plot: Vec<i64> = ordering.iter().map(|&point| predecessor[point]).collect();
+            let clusters = xi_clusters(
+                &reachability_plot,
+                &predecessor_plot,
+                &ordering,
+                xi,
+                min_samples,
+                min_cluster_size,
+                predecessor_correction,
+            );
+            (xi_labels(&ordering, &clusters), Some(clusters))
+        }
+        ExtractionMethod::Dbscan => (dbscan_labels(&reachability, &core_distances, &ordering, eps), None),
+    };
+
+    let core_sample_indices: Vec<usize> = (0..n_samples).filter(|&point| core_distances[point].is_finite()).collect();
+
+    let result = RHash::new();
+    result.aset("labels", RArray::from_vec(labels))?;
+    result.aset("core_sample_indices", RArray::from_vec(core_sample_indices))?;
+    result.aset("ordering", RArray::from_vec(ordering))?;
+    result.aset("reachability", RArray::from_vec(reachability))?;
+    result.aset("core_distances", RArray::from_vec(core_distances))?;
+    result.aset("predecessor", RArray::from_vec(predecessor))?;
+    let cluster_hierarchy = match cluster_hierarchy {
+        Some(clusters) => {
+            let hierarchy = RArray::with_capacity(clusters.len());
+            for (start, end) in clusters {
+                hierarchy.push((start, end))?;
+            }
+            Some(hierarchy)
+        }
+        None => None,
+    };
+    result.aset("cluster_hierarchy", cluster_hierarchy)?;
+    Ok(result)
+}
+
+/// Labels for a DBSCAN-style cut of a fitted OPTICS reachability plot at
+/// `eps`, without recomputing any distances
+pub fn optics_extract_dbscan(
+    reachability: Vec<f64>,
+    core_distances: Vec<f64>,
+    ordering: Vec<usize>,
+    eps: f64,
+) -> Result<RArray, Error> {
+    validate_eps(eps, f64::INFINITY)?;
+    Ok(RArray::from_vec(dbscan_labels(&reachability, &core_distances, &ordering, eps)))
+}
+
+fn validate_eps(eps: f64, max_eps: f64) -> Result<(), Error> {
+    if !(eps > 0.0 && eps.is_finite()) {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("eps must be positive and finite (got {})", eps),
+        ));
+    }
+    if eps > max_eps {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("eps ({}) must not exceed max_eps ({})", eps, max_eps),
+        ));
+    }
+    Ok(())
+}
+
+/// Visit points in OPTICS order: always the unvisited point with the smallest
+/// reachability so far (ties to the lowest index), or the lowest unvisited
+/// index once nothing is reachable. Returns the ordering and, per point, the
+/// reachability distance and predecessor (-1 when unreached)
+fn compute_ordering(
+    index: &NeighborIndex,
+    core_distances: &[f64],
+    max_eps: f64,
+) -> (Vec<usize>, Vec<f64>, Vec<i64>) {
+    let n_samples = core_distances.len();
+    let mut reachability = vec![f64::INFINITY; n_samples];
+    let mut predecessor = vec![-1i64; n_samples];
+    let mut processed = vec![false; n_samples];
+    let mut ordering = Vec::with_capacity(n_samples);
+
+    // Stale entries are skipped when popped rather than removed
+    let mut seeds: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+    let mut next_unreached = 0;
+
+    while ordering.len() < n_samples {
+        let mut point = None;
+        while let Some(Reverse(Candidate(distance, candidate))) = seeds.pop() {
+            if !processed[candidate] && distance == reachability[candidate] {
+                point = Some(candidate);
+                break;
+            }
+        }
+        let point = point.unwrap_or_else(|| {
+            while processed[next_unreached] {
+                next_unreached += 1;
+            }
+            next_unreached
+        });
+
+        processed[point] = true;
+        ordering.push(point);
+
+        if core_distances[point].is_infinite() {
+            continue;
+        }
+        for (neighbor, distance) in index.radius_neighbors(point, max_eps) {
+            if processed[neighbor] {
+                continue;
+            }
+            let reach = distance.max(core_distances[point]);
+            if reach < reachability[neighbor] {
+                reachability[neighbor] = reach;
+                predecessor[neighbor] = point as i64;
+                seeds.push(Reverse(Candidate(reach, neighbor)));
+            }
+        }
+    }
+
+    (ordering, reachability, predecessor)
+}
+
+/// DBSCAN labels at `eps` from the reachability plot: a point far from its
+/// predecessors starts a new cluster if it is a core point and is noise
+/// otherwise; the points after it join its cluster
+fn dbscan_labels(reachability: &[f64], core_distances: &[f64], ordering: &[usize], eps: f64) -> Vec<i64> {
+    let mut labels = vec![-1i64; reachability.len()];
+    let mut label = -1;
+    for &point in ordering {
+        let far = reachability[point] > eps;
+        let near_core = core_distances[point] <= eps;
+        if far && near_core {
+            label += 1;
+        }
+        labels[point] = if far && !near_core { -1 } else { label };
+    }
+    labels
+}
+
+/// Steep down area of the reachability plot with the maximum in between since
+/// it ended
+struct SteepDownArea {
+    start: usize,
+    end: usize,
+    mib: f64,
+}
+
+/// Extend a steep area from `start` while points stay steep, allowing at most
+/// `min_samples` consecutive non-steep points that do not turn back
+fn extend_region(steep: &[bool], xward: &[bool], start: usize, min_samples: usize) -> usize {
+    let mut non_xward_points = 0;
+    let mut end = start;
+    for index in start..steep.len() {
+        if steep[index] {
+            non_xward_points = 0;
+            end = index;
+        } else if !xward[index] {
+            non_xward_points += 1;
+            if non_xward_points > min_samples {
+                break;
+            }
+        } else {
+            return end;
+        }
+    }
+    end
+}
+
+/// Drop steep down areas that can no longer start a cluster after a maximum
+/// of `mib`, and raise the maximum in between of the others
+fn update_filter_sdas(sdas: &mut Vec<SteepDownArea>, mib: f64, xi_complement: f64, reachability_plot: &[f64]) {
+    if mib.is_infinite() {
+        sdas.clear();
+        return;
+    }
+    sdas.retain(|sda| mib <= reachability_plot[sda.start] * xi_complement);
+    for sda in sdas.iter_mut() {
+        sda.mib = sda.mib.max(mib);
+    }
+}
+
+/// Shrink a cluster from the right until its last point's predecessor lies
+/// inside it; None when no such cluster remains
+fn correct_predecessor(
+    reachability_plot: &[f64],
+    predecessor_plot: &[i64],
+    ordering: &[usize],
+    start: usize,
+    mut end: usize,
+) -> Option<(usize, usize)> {
+    while start < end {
+        if reachability_plot[start] > reachability_plot[end] {
+            return Some((start, end));
+        }
+        let predecessor = predecessor_plot[end];
+        if ordering[start..end].iter().any(|&point| point as i64 == predecessor) {
+            return Some((start, end));
+        }
+        end -= 1;
+    }
+    None
+}
+
+/// Clusters of the reachability plot as [start, end] positions, found by
+/// pairing steep down and steep up areas (xi method, with scikit-learn's
+/// corrections to the original paper); nested clusters come before the ones
+/// containing them
+fn xi_clusters(
+    reachability_plot: &[f64],
+    predecessor_plot: &[i64],
+    ordering: &[usize],
+    xi: f64,
+    min_samples: usize,
+    min_cluster_size: usize,
+    predecessor_correction: bool,
+) -> Vec<(usize, usize)> {
+    // A trailing infinity closes clusters that run to the end of the plot
+    let mut plot = reachability_plot.to_vec();
+    plot.push(f64::INFINITY);
+    let xi_complement = 1.0 - xi;
+
+    // 0 / 0 and ∞ / ∞ compare false everywhere, as in the reference
+    let ratio: Vec<f64> = plot.windows(2).map(|pair| pair[0] / pair[1]).collect();
+    let steep_upward: Vec<bool> = ratio.iter().map(|&r| r <= xi_complement).collect();
+    let steep_downward: Vec<bool> = ratio.iter().map(|&r| r >= 1.0 / xi_complement).collect();
+    let downward: Vec<bool> = ratio.iter().map(|&r| r > 1.0).collect();
+    let upward: Vec<bool> = ratio.iter().map(|&r| r < 1.0).collect();
+
+    let mut sdas: Vec<SteepDownArea> = Vec::new();
+    let mut clusters = Vec::new();
+    let mut index = 0;
+    let mut mib = 0.0f64;
+
+    for steep_index in 0..ratio.len() {
+        if !(steep_upward[steep_index] || steep_downward[steep_index]) || steep_index < index {
+            continue;
+        }
+
+        mib = plot[index..=steep_index].iter().fold(mib, |max, &r| max.max(r));
+        update_filter_sdas(&mut sdas, mib, xi_complement, &plot);
+
+        if steep_downward[steep_index] {
+            let end = extend_region(&steep_downward, &upward, steep_index, min_samples);
+            sdas.push(SteepDownArea { start: steep_index, end, mib: 0.0 });
+            index = end + 1;
+            mib = plot[index];
+            continue;
+        }
+
+        let upward_start = steep_index;
+        let upward_end = extend_region(&steep_upward, &downward, upward_start, min_samples);
+        index = upward_end + 1;
+        mib = plot[index];
+
+        let mut upward_clusters = Vec::new();
+        for sda in &sdas {
+            let mut start = sda.start;
+            let mut end = upward_end;
+
+            if plot[end + 1] * xi_complement < sda.mib {
+                continue;
+            }
+
+            // Trim whichever side sits well above the other
+            let down_max = plot[sda.start];
+            if down_max * xi_complement >= plot[end + 1] {
+                while plot[start + 1] > plot[end + 1] && start < sda.end {
+                    start += 1;
+                }
+            } else if plot[end + 1] * xi_complement >= down_max {
+                while plot[end - 1] > down_max && end > upward_start {
+                    end -= 1;
+                }
+            }
+
+            if predecessor_correction {
+                match correct_predecessor(&plot, predecessor_plot, ordering, start, end) {
+                    Some(corrected) => (start, end) = corrected,
+                    None => continue,
+                }
+            }
+
+            if end - start + 1 < min_cluster_size || start > sda.end || end < upward_start {
+                continue;
+            }
+            upward_clusters.push((start, end));
+        }
+
+        // Smaller clusters first
+        upward_clusters.reverse();
+        clusters.extend(upward_clusters);
+    }
+
+    clusters
+}
+
+/// Label points by the first cluster (in hierarchy order) covering them that
+/// does not overlap an already labeled one
+fn xi_labels(ordering: &[usize], clusters: &[(usize, usize)]) -> Vec<i64> {
+    let mut plot_labels = vec![-1i64; ordering.len()];
+    let mut label = 0;
+    for &(start, end) in clusters {
+        if plot_labels[start..=end].iter().all(|&l| l == -1) {
+            plot_labels[start..=end].fill(label);
+            label += 1;
+        }
+    }
+
+    let mut labels = vec![-1i64; ordering.len()];
+    for (position, &point) in ordering.iter().enumerate() {
+        labels[point] = plot_labels[position];
+    }
+    labels
+}
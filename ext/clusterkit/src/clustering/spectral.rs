@@ -0,0 +1,424 @@
+use magnus::{function, prelude::*, Error, Value, RArray, RHash};
+use ndarray::{concatenate, s, Array2, Axis};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use crate::utils::ruby_array_to_ndarray;
+use super::knn;
+use super::metric::Metric;
+use super::{kmeans_best_of, normalize_rows, EmptyClusterPolicy, KMeansAlgorithm, KMeansMetric, KMeansParams};
+
+/// Extra vectors iterated alongside the wanted eigenvectors, speeding up
+/// convergence when the eigenvalue gap after the n_clusters-th is small
+const OVERSAMPLING: usize = 4;
+
+/// LOBPCG iteration limit and tolerance on the eigenvector residuals
+const EIGEN_MAX_ITER: usize = 1000;
+const EIGEN_TOL: f64 = 1e-4;
+
+/// Relative Gram eigenvalue below which a search direction is dropped
+const BASIS_TOL: f64 = 1e-12;
+
+/// Jacobi sweeps for the small projected eigenproblem
+const JACOBI_MAX_SWEEPS: usize = 100;
+
+/// k-means settings for clustering the spectral embedding
+const KMEANS_MAX_ITER: usize = 300;
+const KMEANS_TOL: f64 = 1e-4;
+
+/// Initialize spectral clustering module functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "spectral_rust",
+        function!(spectral, 6),
+    )?;
+
+    Ok(())
+}
+
+/// Perform spectral clustering on an approximate k-nearest-neighbor graph
+/// The `n_neighbors`-NN graph comes from an HNSW index; its symmetrized
+/// connectivity is the affinity, and the eigenvectors of the n_clusters
+/// smallest eigenvalues of the normalized Laplacian, with rows scaled to unit
+/// length, are clustered by k-means with `n_init` initializations
+/// `metric` is "euclidean" (or "l2"), "manhattan" (or "l1"), "cosine",
+/// "chebyshev" or "precomputed", in which case `data` is an n x n distance
+/// matrix and the graph takes each row's nearest entries exactly
+/// Returns a hash with labels, the embedding, the Laplacian eigenvalues in
+/// increasing order, whether the eigensolver converged, and the k-means inertia
+pub fn spectral(
+    data: Value,
+    n_clusters: usize,
+    n_neighbors: usize,
+    metric: String,
+    n_init: usize,
+    random_seed: Option<i64>,
+) -> Result<RHash, Error> {
+    let metric = Metric::parse(&metric)?;
+
+    let data_array = metric.prepare(ruby_array_to_ndarray::<f64>(data)?)?;
+    let n_samples = data_array.nrows();
+
+    if n_clusters == 0 || n_clusters > n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("n_clusters ({}) must be between 1 and the number of samples ({})", n_clusters, n_samples),
+        ));
+    }
+
+    if n_neighbors == 0 || n_neighbors >= n_samples {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            format!("n_neighbors ({}) must be between 1 and the number of samples minus one ({})", n_neighbors, n_samples - 1),
+        ));
+    }
+
+    if n_init == 0 {
+        return Err(Error::new(
+            magnus::exception::arg_error(),
+            "n_init must be at least 1",
+        ));
+    }
+
+    let distance = metric.pairwise(&data_array);
+    let seed = random_seed.map(|seed| seed as u64);
+    let neighbors = knn::knn_graph(&data_array, metric, n_neighbors + 1, seed, &distance);
+    let affinity = normalized_affinity(&neighbors);
+
+    let spectrum = leading_eigenvectors(&affinity, n_clusters, seed);
+    let eigenvalues = spectrum.laplacian_eigenvalues();
+    let mut embedding = spectrum.vectors;
+    normalize_rows(&mut embedding);
+
+    // tol is relative to the embedding, as in kmeans
+    let params = KMeansParams {
+        k: n_clusters,
+        max_iter: KMEANS_MAX_ITER,
+        tol: KMEANS_TOL * embedding.var_axis(Axis(0), 0.0).mean().unwrap_or(0.0),
+        algorithm: KMeansAlgorithm::Lloyd,
+        empty_cluster: EmptyClusterPolicy::Relocate,
+        metric: KMeansMetric::Euclidean,
+    };
+    let best = kmeans_best_of(&embedding, None, &params, random_seed, n_init);
+
+    let result = RHash::new();
+    result.aset("labels", RArray::from_vec(best.labels))?;
+    let embedding_array = RArray::with_capacity(n_samples);
+    for row in embedding.rows() {
+        embedding_array.push(RArray::from_vec(row.to_vec()))?;
+    }
+    result.aset("embedding", embedding_array)?;
+    result.aset("eigenvalues", RArray::from_vec(eigenvalues))?;
+    result.aset("converged", spectrum.converged)?;
+    result.aset("inertia", best.inertia)?;
+    Ok(result)
+}
+
+/// Sparse symmetric matrix: the nonzero (column, value) entries of each row,
+/// by increasing column
+type SparseRows = Vec<Vec<(usize, f64)>>;
+
+/// Normalized affinity S = D^-1/2 A D^-1/2 of the neighbor graph, where
+/// A = (C + Cᵀ) / 2 for the connectivity matrix C of the neighbor lists
+/// (the point itself excluded) and D holds the degrees of A
+fn normalized_affinity(neighbors: &[Vec<(usize, f64)>]) -> SparseRows {
+    let n_samples = neighbors.len();
+    let mut rows: SparseRows = vec![Vec::new(); n_samples];
+    for (i, list) in neighbors.iter().enumerate() {
+        for &(j, _) in list.iter().filter(|&&(j, _)| j != i) {
+            rows[i].push((j, 0.5));
+            rows[j].push((i, 0.5));
+        }
+    }
+
+    // Mutual neighbors appear twice; sum them into one entry of weight 1
+    rows.par_iter_mut().for_each(|row| {
+        row.sort_by_key(|&(j, _)| j);
+        row.dedup_by(|next, kept| {
+            if next.0 == kept.0 {
+                kept.1 += next.1;
+                true
+            } else {
+                false
+            }
+        });
+    });
+
+    // Every point has at least one neighbor, so every degree is positive
+    let scale: Vec<f64> = rows
+        .iter()
+        .map(|row| 1.0 / row.iter().map(|&(_, a)| a).sum::<f64>().sqrt())
+        .collect();
+    rows.par_iter_mut().enumerate().for_each(|(i, row)| {
+        for (j, a) in row.iter_mut() {
+            *a *= scale[i] * scale[*j];
+        }
+    });
+    rows
+}
+
+/// Leading eigenpairs of (I + S) / 2
+struct Spectrum {
+    /// Eigenvalues in decreasing order
+    values: Vec<f64>,
+    /// One eigenvector per column, orthonormal
+    vectors: Array2<f64>,
+    converged: bool,
+}
+
+impl Spectrum {
+    /// Eigenvalues of the normalized Laplacian I - S, in increasing order. It
+    /// shares eigenvectors with (I + S) / 2, at eigenvalue 2 (1 - θ)
+    fn laplacian_eigenvalues(&self) -> Vec<f64> {
+        self.values.iter().map(|&theta| (2.0 * (1.0 - theta)).max(0.0)).collect()
+    }
+}
+
+/// (I + S) / 2 applied to every column of `x`. The shift maps the eigenvalues
+/// of S from [-1, 1] to [0, 1], so the wanted ones are the largest in magnitude
+fn shifted_product(affinity: &SparseRows, x: &Array2<f64>) -> Array2<f64> {
+    let (n_samples, n_columns) = x.dim();
+    let x = x.as_standard_layout();
+    let x_rows = x.as_slice().expect("standard layout");
+    let mut product = vec![0.0; n_samples * n_columns];
+    product.par_chunks_mut(n_columns.max(1)).enumerate().for_each(|(i, row)| {
+        for (value, &xi) in row.iter_mut().zip(&x_rows[i * n_columns..(i + 1) * n_columns]) {
+            *value = 0.5 * xi;
+        }
+        for &(j, s) in &affinity[i] {
+            for (value, &xj) in row.iter_mut().zip(&x_rows[j * n_columns..(j + 1) * n_columns]) {
+                *value += 0.5 * s * xj;
+            }
+        }
+    });
+    Array2::from_shape_vec((n_samples, n_columns), product).expect("one row per sample")
+}
+
+/// The `k` leading eigenpairs of (I + S) / 2 by LOBPCG: each step takes the
+/// best `k + OVERSAMPLING` Ritz vectors from the span of the current vectors,
+/// their residuals and their previous update, until every wanted eigenvector's
+/// residual falls below EIGEN_TOL. Unlike plain subspace iteration this copes
+/// with the tiny eigenvalue gaps of graphs sampled from smooth manifolds
+fn leading_eigenvectors(affinity: &SparseRows, k: usize, seed: Option<u64>) -> Spectrum {
+    let n_samples = affinity.len();
+    let block = (k + OVERSAMPLING).min(n_samples);
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let start = Array2::from_shape_simple_fn((n_samples, block), || rng.gen::<f64>() - 0.5);
+    let (mut values, mut x, mut product) = rayleigh_ritz(affinity, orthonormal_basis(&start), block);
+    let mut previous_update: Option<Array2<f64>> = None;
+
+    let mut converged = false;
+    for _ in 0..EIGEN_MAX_ITER {
+        let mut residuals = product.clone();
+        for (j, mut column) in residuals.columns_mut().into_iter().enumerate() {
+            column.scaled_add(-values[j], &x.column(j));
+        }
+        let largest_residual = (0..k.min(x.ncols()))
+            .map(|j| residuals.column(j).dot(&residuals.column(j)).sqrt())
+            .fold(0.0, f64::max);
+        if largest_residual <= EIGEN_TOL {
+            converged = true;
+            break;
+        }
+
+        let mut blocks = vec![x.view(), residuals.view()];
+        if let Some(update) = &previous_update {
+            blocks.push(update.view());
+        }
+        let search = concatenate(Axis(1), &blocks).expect("blocks have one row per sample");
+        let (next_values, next_x, next_product) = rayleigh_ritz(affinity, orthonormal_basis(&search), block);
+
+        // The part of the step outside the current span
+        previous_update = Some(&next_x - &x.dot(&x.t().dot(&next_x)));
+        x = next_x;
+        values = next_values;
+        product = next_product;
+    }
+
+    values.truncate(k);
+    Spectrum {
+        values,
+        vectors: x.slice(s![.., ..k]).to_owned(),
+        converged,
+    }
+}
+
+/// The `count` leading Ritz pairs of (I + S) / 2 on an orthonormal basis:
+/// their values, the vectors and the product of the matrix with the vectors
+fn rayleigh_ritz(affinity: &SparseRows, basis: Array2<f64>, count: usize) -> (Vec<f64>, Array2<f64>, Array2<f64>) {
+    let basis_product = shifted_product(affinity, &basis);
+    let projected = basis.t().dot(&basis_product);
+    let (mut values, rotation) = symmetric_eigen((&projected + &projected.t()) * 0.5);
+    let count = count.min(values.len());
+    values.truncate(count);
+    let rotation = rotation.slice(s![.., ..count]);
+    (values, basis.dot(&rotation), basis_product.dot(&rotation))
+}
+
+/// Orthonormal basis for the span of the columns, from the eigenvectors of
+/// their Gram matrix (applied twice to restore orthogonality to working
+/// precision). Directions the columns barely span are dropped
+fn orthonormal_basis(columns: &Array2<f64>) -> Array2<f64> {
+    let nonzero: Vec<usize> = (0..columns.ncols())
+        .filter(|&j| {
+            let norm = columns.column(j).dot(&columns.column(j));
+            norm > 0.0 && norm.is_finite()
+        })
+        .collect();
+    let mut basis = columns.select(Axis(1), &nonzero);
+    for mut column in basis.columns_mut() {
+        let norm = column.dot(&column).sqrt();
+        column.mapv_inplace(|value| value / norm);
+    }
+
+    for _ in 0..2 {
+        let (values, vectors) = symmetric_eigen(basis.t().dot(&basis));
+        let largest = values.first().copied().unwrap_or(0.0);
+        let rank = values.iter().take_while(|&&value| value > BASIS_TOL * largest).count();
+        let mut rotation = vectors.slice(s![.., ..rank]).to_owned();
+        for (mut column, value) in rotation.columns_mut().into_iter().zip(&values) {
+            column.mapv_inplace(|entry| entry / value.sqrt());
+        }
+        basis = basis.dot(&rotation);
+    }
+    basis
+}
+
+/// Eigenvalues, in decreasing order, and eigenvectors (as columns) of a small
+/// symmetric matrix by cyclic Jacobi rotations
+fn symmetric_eigen(mut a: Array2<f64>) -> (Vec<f64>, Array2<f64>) {
+    let size = a.nrows();
+    let mut v = Array2::<f64>::eye(size);
+
+    for _ in 0..JACOBI_MAX_SWEEPS {
+        let off_diagonal: f64 = (0..size)
+            .flat_map(|p| (p + 1..size).map(move |q| (p, q)))
+            .map(|(p, q)| a[[p, q]] * a[[p, q]])
+            .sum();
+        let scale: f64 = a.iter().map(|x| x * x).sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * scale {
+            break;
+        }
+
+        for p in 0..size {
+            for q in p + 1..size {
+                if a[[p, q]] == 0.0 {
+                    continue;
+                }
+                // Rotation zeroing a[p][q]
+                let tau = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+                let t = tau.signum() / (tau.abs() + (1.0 + tau * tau).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = t * c;
+
+                for r in 0..size {
+                    let (arp, arq) = (a[[r, p]], a[[r, q]]);
+                    a[[r, p]] = c * arp - s * arq;
+                    a[[r, q]] = s * arp + c * arq;
+                }
+                for r in 0..size {
+                    let (apr, aqr) = (a[[p, r]], a[[q, r]]);
+                    a[[p, r]] = c * apr - s * aqr;
+                    a[[q, r]] = s * apr + c * aqr;
+                }
+                for r in 0..size {
+                    let (vrp, vrq) = (v[[r, p]], v[[r, q]]);
+                    v[[r, p]] = c * vrp - s * vrq;
+                    v[[r, q]] = s * vrp + c * vrq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..size).collect();
+    order.sort_by(|&i, &j| a[[j, j]].total_cmp(&a[[i, i]]));
+    let values = order.iter().map(|&i| a[[i, i]]).collect();
+    let vectors = v.select(Axis(1), &order);
+    (values, vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Neighbor lists, the point itself first, of the graph with these undirected edges
+    fn neighbor_lists(n_samples: usize, edges: &[(usize, usize)]) -> Vec<Vec<(usize, f64)>> {
+        let mut lists: Vec<Vec<(usize, f64)>> = (0..n_samples).map(|i| vec![(i, 0.0)]).collect();
+        for &(i, j) in edges {
+            lists[i].push((j, 1.0));
+            lists[j].push((i, 1.0));
+        }
+        lists
+    }
+
+    fn clique_edges(members: std::ops::Range<usize>) -> Vec<(usize, usize)> {
+        members.clone().flat_map(|i| (i + 1..members.end).map(move |j| (i, j))).collect()
+    }
+
+    #[test]
+    fn symmetric_eigen_matches_a_known_spectrum() {
+        let a = ndarray::arr2(&[[2.0, -1.0, 0.0], [-1.0, 2.0, -1.0], [0.0, -1.0, 2.0]]);
+        let (values, vectors) = symmetric_eigen(a.clone());
+
+        let expected = [2.0 + 2f64.sqrt(), 2.0, 2.0 - 2f64.sqrt()];
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12, "{} != {}", value, expected);
+        }
+        for (j, &value) in values.iter().enumerate() {
+            let residual = a.dot(&vectors.column(j)) - &vectors.column(j) * value;
+            assert!(residual.iter().all(|x| x.abs() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn orthonormal_basis_drops_dependent_columns() {
+        let columns = ndarray::arr2(&[[1.0, 1.0, 2.0, 0.0], [0.0, 1.0, 1.0, 0.0], [0.0, 0.0, 0.0, 0.0]]);
+        let basis = orthonormal_basis(&columns);
+
+        assert_eq!(basis.ncols(), 2);
+        let gram = basis.t().dot(&basis);
+        assert!((&gram - &Array2::<f64>::eye(2)).iter().all(|x| x.abs() < 1e-12));
+    }
+
+    #[test]
+    fn path_graph_has_the_known_laplacian_spectrum() {
+        // The normalized Laplacian of a path on n nodes has eigenvalues 1 - cos(π j / (n - 1))
+        let n_samples = 30;
+        let edges: Vec<(usize, usize)> = (1..n_samples).map(|i| (i - 1, i)).collect();
+        let affinity = normalized_affinity(&neighbor_lists(n_samples, &edges));
+        let spectrum = leading_eigenvectors(&affinity, 3, Some(42));
+
+        assert!(spectrum.converged);
+        for (j, value) in spectrum.laplacian_eigenvalues().into_iter().enumerate() {
+            let expected = 1.0 - (std::f64::consts::PI * j as f64 / (n_samples - 1) as f64).cos();
+            assert!((value - expected).abs() < 1e-6, "eigenvalue {}: {} != {}", j, value, expected);
+        }
+    }
+
+    #[test]
+    fn disconnected_cliques_give_one_zero_eigenvalue_each() {
+        // The normalized Laplacian of a clique on m nodes has eigenvalues 0 and m / (m - 1)
+        let mut edges = clique_edges(0..5);
+        edges.extend(clique_edges(5..12));
+        let affinity = normalized_affinity(&neighbor_lists(12, &edges));
+        let spectrum = leading_eigenvectors(&affinity, 3, Some(42));
+
+        assert!(spectrum.converged);
+        let values = spectrum.laplacian_eigenvalues();
+        let expected = [0.0, 0.0, 7.0 / 6.0];
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+        }
+
+        // The two null vectors are constant on each clique
+        for j in 0..2 {
+            let column = spectrum.vectors.column(j);
+            assert!((1..5).all(|i| (column[i] - column[0]).abs() < 1e-4));
+            assert!((6..12).all(|i| (column[i] - column[5]).abs() < 1e-4));
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use magnus::{function, prelude::*, Error, Value};
+use ndarray::{Array1, Array2, Axis, CowArray, Ix2};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use crate::utils::ruby_array_to_ndarray;
+use super::squared_euclidean_distance;
+
+/// Initialize cluster validity metric functions
+pub fn init(clustering_module: &magnus::RModule) -> Result<(), Error> {
+    clustering_module.define_singleton_method(
+        "silhouette_score_rust",
+        function!(silhouette_score, 5),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "davies_bouldin_score_rust",
+        function!(davies_bouldin_score, 3),
+    )?;
+
+    clustering_module.define_singleton_method(
+        "calinski_harabasz_score_rust",
+        function!(calinski_harabasz_score, 3),
+    )?;
+
+    Ok(())
+}
+
+/// Data and labels ready for scoring: noise rows removed if requested and
+/// labels renumbered 0..n_clusters. The data is borrowed when scoring an
+/// array that is already in memory
+pub(super) struct LabeledData<'a> {
+    data: CowArray<'a, f64, Ix2>,
+    labels: Vec<usize>,
+    n_clusters: usize,
+}
+
+impl<'a> LabeledData<'a> {
+    fn new(data: Value, labels: Vec<i64>, exclude_noise: bool) -> Result<Self, Error> {
+        let data = ruby_array_to_ndarray::<f64>(data)?;
+        if labels.len() != data.nrows() {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("labels has {} entries but data has {} samples", labels.len(), data.nrows()),
+            ));
+        }
+
+        // Noise is labeled -1 (or any negative label) by HDBSCAN and DBSCAN
+        let kept: Vec<usize> = (0..labels.len())
+            .filter(|&i| !exclude_noise || labels[i] >= 0)
+            .collect();
+
+        Ok(Self::renumbered(
+            data.select(Axis(0), &kept),
+            kept.iter().map(|&i| labels[i]).collect(),
+        ))
+    }
+
+    /// Renumber labels to 0..n_clusters in order of label value
+    pub(super) fn renumbered(data: impl Into<CowArray<'a, f64, Ix2>>, labels: Vec<i64>) -> Self {
+        let mut ids = BTreeMap::new();
+        for &label in &labels {
+            let next = ids.len();
+            ids.entry(label).or_insert(next);
+        }
+
+        LabeledData {
+            data: data.into(),
+            labels: labels.iter().map(|label| ids[label]).collect(),
+            n_clusters: ids.len(),
+        }
+    }
+
+    /// Random subset of `sample_size` points
+    fn sample(&self, sample_size: usize, random_seed: Option<i64>) -> LabeledData<'static> {
+        let mut rng: Box<dyn RngCore> = match random_seed {
+            Some(seed) => Box::new(StdRng::seed_from_u64(seed as u64)),
+            None => Box::new(thread_rng()),
+        };
+        let mut indices = rand::seq::index::sample(&mut rng, self.n_samples(), sample_size).into_vec();
+        indices.sort_unstable();
+
+        // Renumber, since a sample may miss whole clusters
+        LabeledData::renumbered(
+            self.data.select(Axis(0), &indices),
+            indices.iter().map(|&i| self.labels[i] as i64).collect(),
+        )
+    }
+
+    fn n_samples(&self) -> usize {
+        self.labels.len()
+    }
+
+    fn require_clusters(&self, metric: &str) -> Result<(), Error> {
+        if self.n_clusters < 2 || self.n_clusters >= self.n_samples() {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!(
+                    "{} needs between 2 and n_samples - 1 clusters (got {} clusters for {} samples)",
+                    metric, self.n_clusters, self.n_samples()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Per-cluster means and sizes
+    fn centroids(&self) -> (Array2<f64>, Vec<usize>) {
+        let mut centroids = Array2::<f64>::zeros((self.n_clusters, self.data.ncols()));
+        let mut counts = vec![0usize; self.n_clusters];
+        for (row, &label) in self.data.rows().into_iter().zip(&self.labels) {
+            let mut centroid = centroids.row_mut(label);
+            centroid += &row;
+            counts[label] += 1;
+        }
+        for (mut centroid, &count) in centroids.rows_mut().into_iter().zip(&counts) {
+            centroid /= count as f64;
+        }
+        (centroids, counts)
+    }
+}
+
+/// Mean silhouette coefficient using Euclidean distance
+/// With `sample_size`, the score is computed on a random subset of that many
+/// points, which bounds the O(n²) cost on large datasets
+/// Points alone in their cluster score 0; fewer than two clusters scores 0
+fn silhouette_score(
+    data: Value,
+    labels: Vec<i64>,
+    exclude_noise: bool,
+    sample_size: Option<usize>,
+    random_seed: Option<i64>,
+) -> Result<f64, Error> {
+    let labeled = LabeledData::new(data, labels, exclude_noise)?;
+
+    if let Some(sample_size) = sample_size {
+        if sample_size < 2 {
+            return Err(Error::new(
+                magnus::exception::arg_error(),
+                format!("sample_size must be at least 2 (got {})", sample_size),
+            ));
+        }
+        if sample_size < labeled.n_samples() {
+            return Ok(silhouette(&labeled.sample(sample_size, random_seed)));
+        }
+    }
+
+    Ok(silhouette(&labeled))
+}
+
+/// Exact mean silhouette coefficient, parallel over points
+pub(super) fn silhouette(labeled: &LabeledData) -> f64 {
+    if labeled.n_clusters < 2 {
+        return 0.0;
+    }
+
+    let mut counts = vec![0usize; labeled.n_clusters];
+    for &label in &labeled.labels {
+        counts[label] += 1;
+    }
+
+    let data = &labeled.data;
+    let labels = &labeled.labels;
+    let scores: Vec<f64> = (0..labeled.n_samples())
+        .into_par_iter()
+        .map(|i| {
+            let own = labels[i];
+            if counts[own] == 1 {
+                return 0.0;
+            }
+
+            let mut sums = vec![0.0; counts.len()];
+            for (j, &label) in labels.iter().enumerate() {
+                sums[label] += squared_euclidean_distance(&data.row(i), &data.row(j)).sqrt();
+            }
+
+            let a = sums[own] / (counts[own] - 1) as f64;
+            let b = (0..counts.len())
+                .filter(|&c| c != own)
+                .map(|c| sums[c] / counts[c] as f64)
+                .fold(f64::INFINITY, f64::min);
+
+            let scale = a.max(b);
+            if scale > 0.0 { (b - a) / scale } else { 0.0 }
+        })
+        .collect();
+
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+/// Davies–Bouldin index: average over clusters of the worst ratio of
+/// within-cluster scatter to between-centroid separation. Lower is better
+fn davies_bouldin_score(data: Value, labels: Vec<i64>, exclude_noise: bool) -> Result<f64, Error> {
+    let labeled = LabeledData::new(data, labels, exclude_noise)?;
+    labeled.require_clusters("Davies-Bouldin score")?;
+    Ok(davies_bouldin(&labeled))
+}
+
+fn davies_bouldin(labeled: &LabeledData) -> f64 {
+    let (centroids, counts) = labeled.centroids();
+    let n_clusters = labeled.n_clusters;
+
+    // Mean distance from each cluster's points to its centroid
+    let distances: Vec<f64> = labeled
+        .labels
+        .par_iter()
+        .enumerate()
+        .map(|(i, &label)| squared_euclidean_distance(&labeled.data.row(i), &centroids.row(label)).sqrt())
+        .collect();
+    let mut scatter = vec![0.0; n_clusters];
+    for (&label, distance) in labeled.labels.iter().zip(&distances) {
+        scatter[label] += distance;
+    }
+    for (s, &count) in scatter.iter_mut().zip(&counts) {
+        *s /= count as f64;
+    }
+
+    let mut separation = Array2::<f64>::zeros((n_clusters, n_clusters));
+    for a in 0..n_clusters {
+        for b in (a + 1)..n_clusters {
+            let d = squared_euclidean_distance(&centroids.row(a), &centroids.row(b)).sqrt();
+            separation[[a, b]] = d;
+            separation[[b, a]] = d;
+        }
+    }
+
+    if scatter.iter().all(|&s| s == 0.0) || separation.iter().all(|&d| d == 0.0) {
+        return 0.0;
+    }
+
+    let total: f64 = (0..n_clusters)
+        .map(|a| {
+            (0..n_clusters)
+                .filter(|&b| b != a && separation[[a, b]] > 0.0)
+                .map(|b| (scatter[a] + scatter[b]) / separation[[a, b]])
+                .fold(0.0, f64::max)
+        })
+        .sum();
+
+    total / n_clusters as f64
+}
+
+/// Calinski–Harabasz index: ratio of between-cluster to within-cluster
+/// dispersion, scaled by degrees of freedom. Higher is better
+fn calinski_harabasz_score(data: Value, labels: Vec<i64>, exclude_noise: bool) -> Result<f64, Error> {
+    let labeled = LabeledData::new(data, labels, exclude_noise)?;
+    labeled.require_clusters("Calinski-Harabasz score")?;
+    Ok(calinski_harabasz(&labeled))
+}
+
+fn calinski_harabasz(labeled: &LabeledData) -> f64 {
+    let (centroids, counts) = labeled.centroids();
+    let n_samples = labeled.n_samples() as f64;
+    let n_clusters = labeled.n_clusters as f64;
+    let mean: Array1<f64> = labeled.data.mean_axis(Axis(0)).expect("data is not empty");
+
+    let between: f64 = centroids
+        .rows()
+        .into_iter()
+        .zip(&counts)
+        .map(|(centroid, &count)| count as f64 * squared_euclidean_distance(&centroid, &mean.view()))
+        .sum();
+
+    let within_per_point: Vec<f64> = labeled
+        .labels
+        .par_iter()
+        .enumerate()
+        .map(|(i, &label)| squared_euclidean_distance(&labeled.data.row(i), &centroids.row(label)))
+        .collect();
+    let within: f64 = within_per_point.iter().sum();
+
+    if within == 0.0 {
+        return 1.0;
+    }
+
+    between * (n_samples - n_clusters) / (within * (n_clusters - 1.0))
+}
@@ -1,13 +1,14 @@
 use magnus::{Error, RArray, RHash, Value, TryConvert, Integer, Module, Object};
 use magnus::value::ReprValue;
-use hnsw_rs::prelude::*;
 use annembed::prelude::*;
 use std::fs::File;
 use std::io::{Write, Read};
 use std::cell::RefCell;
 use bincode;
 use serde::{Serialize, Deserialize};
-use crate::utils::ruby_array_to_vec_vec_f32;
+use crate::errors::{base_error, convergence_error, dimension_mismatch_error, not_fitted_error, persistence_error};
+use crate::hnsw::build_l2_index;
+use crate::utils::ruby_array_to_matrix;
 
 // Simple struct to serialize UMAP results
 #[derive(Serialize, Deserialize)]
@@ -126,41 +127,16 @@ impl RustUMAP {
 
     fn fit_transform(&self, data: Value) -> Result<RArray, Error> {
         // Convert Ruby array to Rust Vec<Vec<f32>> using shared helper
-        let data_f32 = ruby_array_to_vec_vec_f32(data)?;
+        let data_f32 = ruby_array_to_matrix::<f32>(data)?;
 
-        // Build HNSW graph
+        // Build HNSW graph; a seed inserts serially for reproducibility
         let ef_c = 50;
         let max_nb_connection = 70;
-        let nb_points = data_f32.len();
-        let nb_layer = 16.min((nb_points as f32).ln().trunc() as usize);
-
-        // Create HNSW with or without seed
-        let hnsw = match self.random_seed {
-            Some(seed) => Hnsw::<f32, DistL2>::new_with_seed(
-                max_nb_connection, nb_points, nb_layer, ef_c, DistL2 {}, seed
-            ),
-            None => Hnsw::<f32, DistL2>::new(
-                max_nb_connection, nb_points, nb_layer, ef_c, DistL2 {}
-            ),
-        };
-
-        // Insert data into HNSW
-        let data_with_id: Vec<(&Vec<f32>, usize)> = data_f32.iter()
-            .enumerate()
-            .map(|(i, v)| (v, i))
-            .collect();
-        
-        // Use serial_insert for reproducibility when seed is provided,
-        // parallel_insert for performance when no seed
-        if self.random_seed.is_some() {
-            hnsw.serial_insert(&data_with_id);
-        } else {
-            hnsw.parallel_insert(&data_with_id);
-        }
+        let hnsw = build_l2_index(&data_f32, max_nb_connection, ef_c, self.random_seed);
 
         // Create KGraph from HNSW
         let kgraph: annembed::fromhnsw::kgraph::KGraph<f32> = annembed::fromhnsw::kgraph::kgraph_from_hnsw_all(&hnsw, self.n_neighbors)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| base_error(e.to_string()))?;
 
         // Set up embedding parameters
         let mut embed_params = EmbedderParams::default();
@@ -179,11 +155,10 @@ impl RustUMAP {
         let mut embedder = Embedder::new(&kgraph, embed_params);
 
         let embed_result = embedder.embed()
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), 
-                format!("Embedding failed: {}", e)))?;
+            .map_err(|e| convergence_error(format!("Embedding failed: {}", e)))?;
 
         if embed_result == 0 {
-            return Err(Error::new(magnus::exception::runtime_error(), "No points were embedded"));
+            return Err(convergence_error("No points were embedded"));
         }
 
         // Get embedded data
@@ -220,9 +195,9 @@ impl RustUMAP {
         let training_embeddings = self.training_embeddings.borrow();
 
         let training_data_ref = training_data.as_ref()
-            .ok_or_else(|| Error::new(magnus::exception::runtime_error(), "No model to save. Run fit_transform first."))?;
+            .ok_or_else(|| not_fitted_error("No model to save. Run fit_transform first."))?;
         let training_embeddings_ref = training_embeddings.as_ref()
-            .ok_or_else(|| Error::new(magnus::exception::runtime_error(), "No embeddings to save."))?;
+            .ok_or_else(|| not_fitted_error("No embeddings to save."))?;
 
         let saved_model = SavedUMAPModel {
             n_components: self.n_components,
@@ -234,13 +209,13 @@ impl RustUMAP {
         };
 
         let serialized = bincode::serialize(&saved_model)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         let mut file = File::create(&path)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         file.write_all(&serialized)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         Ok(())
     }
@@ -248,14 +223,14 @@ impl RustUMAP {
     // Load a full model for transforming new data
     fn load_model(path: String) -> Result<Self, Error> {
         let mut file = File::open(&path)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         let saved_model: SavedUMAPModel = bincode::deserialize(&buffer)
-            .map_err(|e| Error::new(magnus::exception::runtime_error(), e.to_string()))?;
+            .map_err(|e| persistence_error(e.to_string()))?;
 
         Ok(RustUMAP {
             n_components: saved_model.n_components,
@@ -275,12 +250,18 @@ impl RustUMAP {
         let training_embeddings = self.training_embeddings.borrow();
 
         let training_data_ref = training_data.as_ref()
-            .ok_or_else(|| Error::new(magnus::exception::runtime_error(), "No model loaded. Load a model or run fit_transform first."))?;
+            .ok_or_else(|| not_fitted_error("No model loaded. Load a model or run fit_transform first."))?;
         let training_embeddings_ref = training_embeddings.as_ref()
-            .ok_or_else(|| Error::new(magnus::exception::runtime_error(), "No embeddings available."))?;
+            .ok_or_else(|| not_fitted_error("No embeddings available."))?;
 
         // Convert input data to Rust format using shared helper
-        let new_data = ruby_array_to_vec_vec_f32(data)?;
+        let new_data = ruby_array_to_matrix::<f32>(data)?;
+        let n_features = training_data_ref.first().map_or(0, |row| row.len());
+        if new_data[0].len() != n_features {
+            return Err(dimension_mismatch_error(format!(
+                "Data has {} features but the model was fitted on {}", new_data[0].len(), n_features
+            )));
+        }
 
         // For each new point, find k nearest neighbors in training data
         // and average their embeddings (weighted by distance)
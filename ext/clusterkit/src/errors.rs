@@ -0,0 +1,89 @@
+use magnus::{prelude::*, value::Lazy, Error, ExceptionClass, RModule, Ruby};
+use std::borrow::Cow;
+
+// Exception classes are defined in `init` and looked up lazily by name so
+// every module raises the same class objects Ruby code rescues.
+static BASE_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "Error"));
+static INVALID_INPUT_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "InvalidInputError"));
+static DIMENSION_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "DimensionError"));
+static DIMENSION_MISMATCH_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "DimensionMismatchError"));
+static CONVERGENCE_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "ConvergenceError"));
+static NOT_FITTED_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "NotFittedError"));
+static PERSISTENCE_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| lookup_error_class(ruby, "PersistenceError"));
+
+/// Define the ClusterKit exception hierarchy:
+///
+///   ClusterKit::Error < StandardError
+///     ClusterKit::InvalidInputError
+///       ClusterKit::DimensionError
+///         ClusterKit::DimensionMismatchError
+///     ClusterKit::ConvergenceError
+///     ClusterKit::NotFittedError
+///     ClusterKit::PersistenceError
+pub fn init(parent: &RModule) -> Result<(), Error> {
+    let base_error = parent.define_error("Error", magnus::exception::standard_error())?;
+    let invalid_input_error = parent.define_error("InvalidInputError", base_error)?;
+    let dimension_error = parent.define_error("DimensionError", invalid_input_error)?;
+    parent.define_error("DimensionMismatchError", dimension_error)?;
+    parent.define_error("ConvergenceError", base_error)?;
+    parent.define_error("NotFittedError", base_error)?;
+    parent.define_error("PersistenceError", base_error)?;
+
+    Ok(())
+}
+
+fn lookup_error_class(ruby: &Ruby, name: &str) -> ExceptionClass {
+    ruby.class_object()
+        .const_get::<_, RModule>("ClusterKit")
+        .and_then(|module| module.const_get(name))
+        .unwrap_or_else(|_| panic!("ClusterKit::{} is not defined", name))
+}
+
+/// Generic ClusterKit::Error for failures that fit no more specific class
+pub fn base_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&BASE_ERROR), message)
+}
+
+/// Input data is malformed (empty, not a 2D array, non-numeric values)
+pub fn invalid_input_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&INVALID_INPUT_ERROR), message)
+}
+
+/// Input data has an inconsistent shape (ragged rows)
+pub fn dimension_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&DIMENSION_ERROR), message)
+}
+
+/// Input does not match the dimension of a fitted model or index
+pub fn dimension_mismatch_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&DIMENSION_MISMATCH_ERROR), message)
+}
+
+/// An iterative algorithm failed to produce a result
+pub fn convergence_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&CONVERGENCE_ERROR), message)
+}
+
+/// A model was used before being fitted or loaded
+pub fn not_fitted_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&NOT_FITTED_ERROR), message)
+}
+
+/// Saving or loading a model or index failed
+pub fn persistence_error(message: impl Into<Cow<'static, str>>) -> Error {
+    let ruby = Ruby::get().unwrap();
+    Error::new(ruby.get_inner(&PERSISTENCE_ERROR), message)
+}
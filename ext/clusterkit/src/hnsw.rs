@@ -9,6 +9,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use serde::{Serialize, Deserialize};
 use std::fs::File;
+use crate::errors::{invalid_input_error, persistence_error};
+use crate::utils::ruby_array_to_vector;
 
 // Store metadata alongside vectors
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -96,14 +98,12 @@ impl HnswIndex {
         let distance_type = match space.as_str() {
             "euclidean" => DistanceType::Euclidean,
             "cosine" => {
-                return Err(Error::new(
-                    exception::runtime_error(),
+                return Err(invalid_input_error(
                     "Cosine distance is not yet implemented, please use :euclidean"
                 ));
             },
             "inner_product" => {
-                return Err(Error::new(
-                    exception::runtime_error(),
+                return Err(invalid_input_error(
                     "Inner product distance is not yet implemented, please use :euclidean"
                 ));
             },
@@ -134,7 +134,7 @@ impl HnswIndex {
     // Add a single item to the index
     pub fn add_item(&self, vector: RArray, kwargs: RHash) -> Result<Value, Error> {
         // Parse vector
-        let vec_data = parse_vector(vector, self.dim)?;
+        let vec_data = ruby_array_to_vector::<f32>(vector, Some(self.dim))?;
         
         // Get or generate label
         let label: String = if let Some(v) = kwargs.delete(Symbol::new("label"))? {
@@ -214,7 +214,7 @@ impl HnswIndex {
         
         for (i, vector) in vectors.each().enumerate() {
             let vector: RArray = TryConvert::try_convert(vector?)?;
-            let vec_data = parse_vector(vector, self.dim)?;
+            let vec_data = ruby_array_to_vector::<f32>(vector, Some(self.dim))?;
             
             // Get or generate label
             let label = if let Some(ref labels_array) = labels {
@@ -290,7 +290,7 @@ impl HnswIndex {
         };
         
         // Parse query vector
-        let query_vec = parse_vector(query, self.dim)?;
+        let query_vec = ruby_array_to_vector::<f32>(query, Some(self.dim))?;
         
         // Set search ef if provided
         if let Some(v) = kwargs.delete(Symbol::new("ef"))? {
@@ -339,7 +339,7 @@ impl HnswIndex {
         };
         
         // Parse query vector
-        let query_vec = parse_vector(query, self.dim)?;
+        let query_vec = ruby_array_to_vector::<f32>(query, Some(self.dim))?;
         
         // Perform search
         let neighbors = {
@@ -432,7 +432,7 @@ impl HnswIndex {
         // Load metadata first to get dimensions and space
         let metadata_path = format!("{}.metadata", path_str);
         let metadata_file = File::open(&metadata_path)
-            .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to open metadata file: {}", e)))?;
+            .map_err(|e| persistence_error(format!("Failed to open metadata file: {}", e)))?;
         
         let (
             _metadata_store,
@@ -447,7 +447,7 @@ impl HnswIndex {
             usize,
             String,  // Changed from &str to String for deserialization
         ) = bincode::deserialize_from(metadata_file)
-            .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to load metadata: {}", e)))?;
+            .map_err(|e| persistence_error(format!("Failed to load metadata: {}", e)))?;
         
         // Load HNSW structure
         let hnsw_dir = format!("{}_hnsw_data", path_str);
@@ -461,7 +461,7 @@ impl HnswIndex {
         
         // Now we can load the HNSW with 'static lifetime
         let hnsw: Hnsw<'static, f32, DistL2> = hnswio_static.load_hnsw()
-            .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to load HNSW index: {}", e)))?;
+            .map_err(|e| persistence_error(format!("Failed to load HNSW index: {}", e)))?;
         
         // Use the loaded metadata
         let metadata_store = _metadata_store;
@@ -472,7 +472,7 @@ impl HnswIndex {
             "euclidean" => DistanceType::Euclidean,
             "cosine" => DistanceType::Cosine,
             "inner_product" => DistanceType::InnerProduct,
-            _ => return Err(Error::new(exception::runtime_error(), "Unknown distance type in saved file")),
+            _ => return Err(persistence_error("Unknown distance type in saved file")),
         };
         
         // Use default ef_construction as ef_search
@@ -496,13 +496,13 @@ impl HnswIndex {
         // Create directory for HNSW structure
         let hnsw_dir = format!("{}_hnsw_data", path_str);
         std::fs::create_dir_all(&hnsw_dir)
-            .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to create directory: {}", e)))?;
+            .map_err(|e| persistence_error(format!("Failed to create directory: {}", e)))?;
         
         // Save HNSW structure
         {
             let hnsw = self.hnsw.lock().unwrap();
             hnsw.file_dump(&std::path::Path::new(&hnsw_dir), "hnsw")
-                .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to save HNSW: {}", e)))?;
+                .map_err(|e| persistence_error(format!("Failed to save HNSW: {}", e)))?;
         }
         
         // Save metadata
@@ -525,36 +525,16 @@ impl HnswIndex {
             );
             
             let file = File::create(&metadata_path)
-                .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to create metadata file: {}", e)))?;
+                .map_err(|e| persistence_error(format!("Failed to create metadata file: {}", e)))?;
             
             bincode::serialize_into(file, &metadata_data)
-                .map_err(|e| Error::new(exception::runtime_error(), format!("Failed to save metadata: {}", e)))?;
+                .map_err(|e| persistence_error(format!("Failed to save metadata: {}", e)))?;
         }
         
         Ok(value::qnil().as_value())
     }
 }
 
-// Helper function to parse a Ruby array into a Vec<f32>
-fn parse_vector(array: RArray, expected_dim: usize) -> Result<Vec<f32>, Error> {
-    let len = array.len();
-    if len != expected_dim {
-        return Err(Error::new(
-            exception::arg_error(),
-            format!("Vector dimension mismatch: expected {}, got {}", expected_dim, len)
-        ));
-    }
-    
-    let mut vec = Vec::with_capacity(len);
-    for item in array.each() {
-        let value: f64 = TryConvert::try_convert(item?)
-            .map_err(|_| Error::new(exception::type_error(), "Vector elements must be numeric"))?;
-        vec.push(value as f32);
-    }
-    
-    Ok(vec)
-}
-
 // Helper function to parse metadata
 fn parse_metadata(value: Value) -> Result<HashMap<String, String>, Error> {
     let hash: RHash = TryConvert::try_convert(value)
@@ -592,6 +572,34 @@ fn parse_metadata(value: Value) -> Result<HashMap<String, String>, Error> {
     Ok(metadata)
 }
 
+// Build an L2 index over all the points at once, with as many layers as their
+// number calls for. A seed makes the graph reproducible by inserting serially
+pub(crate) fn build_l2_index(
+    points: &[Vec<f32>],
+    max_nb_connection: usize,
+    ef_construction: usize,
+    random_seed: Option<u64>,
+) -> Hnsw<'static, f32, DistL2> {
+    let nb_points = points.len();
+    let nb_layer = 16.min((nb_points as f32).ln().trunc() as usize).max(1);
+    let hnsw = match random_seed {
+        Some(seed) => Hnsw::<f32, DistL2>::new_with_seed(
+            max_nb_connection, nb_points, nb_layer, ef_construction, DistL2 {}, seed
+        ),
+        None => Hnsw::<f32, DistL2>::new(
+            max_nb_connection, nb_points, nb_layer, ef_construction, DistL2 {}
+        ),
+    };
+
+    let data_with_id: Vec<(&Vec<f32>, usize)> = points.iter().enumerate().map(|(i, v)| (v, i)).collect();
+    if random_seed.is_some() {
+        hnsw.serial_insert(&data_with_id);
+    } else {
+        hnsw.parallel_insert(&data_with_id);
+    }
+    hnsw
+}
+
 // Initialize the HNSW module
 pub fn init(parent: &magnus::RModule) -> Result<(), Error> {
     let class = parent.define_class("HNSW", class::object())?;
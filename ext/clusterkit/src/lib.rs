@@ -1,6 +1,7 @@
 use magnus::{define_module, Error};
 
 mod embedder;
+mod errors;
 mod svd;
 mod utils;
 mod clustering;
@@ -13,6 +14,9 @@ mod tests;
 fn init() -> Result<(), Error> {
     let module = define_module("ClusterKit")?;
     
+    // Exception classes must exist before any submodule can raise them
+    errors::init(&module)?;
+    
     // Initialize submodules
     embedder::init(&module)?;
     svd::init(&module)?;
@@ -1,6 +1,7 @@
 use magnus::{function, prelude::*, Error, Value, RArray};
 use annembed::tools::svdapprox::{SvdApprox, RangeApproxMode, RangeRank, MatRepr};
-use crate::utils::ruby_array_to_ndarray2;
+use crate::errors::{base_error, convergence_error};
+use crate::utils::ruby_array_to_ndarray;
 
 pub fn init(parent: &magnus::RModule) -> Result<(), Error> {
     let svd_module = parent.define_module("SVD")?;
@@ -15,7 +16,7 @@ pub fn init(parent: &magnus::RModule) -> Result<(), Error> {
 
 fn randomized_svd(matrix: Value, k: usize, n_iter: usize) -> Result<RArray, Error> {
     // Convert Ruby array to ndarray using shared helper
-    let matrix_data = ruby_array_to_ndarray2(matrix)?;
+    let matrix_data = ruby_array_to_ndarray::<f64>(matrix)?;
     let (n_rows, n_cols) = matrix_data.dim();
     
     if k > n_rows.min(n_cols) {
@@ -37,19 +38,19 @@ fn randomized_svd(matrix: Value, k: usize, n_iter: usize) -> Result<RArray, Erro
     
     // Perform SVD
     let svd_result = svd_approx.direct_svd(params)
-        .map_err(|e| Error::new(magnus::exception::runtime_error(), e))?;
+        .map_err(convergence_error)?;
     
     // Extract U, S, V from the result - they are optional fields
     let u_matrix = svd_result.u.ok_or_else(|| {
-        Error::new(magnus::exception::runtime_error(), "No U matrix in SVD result")
+        base_error("No U matrix in SVD result")
     })?;
     
     let s_values = svd_result.s.ok_or_else(|| {
-        Error::new(magnus::exception::runtime_error(), "No S values in SVD result")
+        base_error("No S values in SVD result")
     })?;
     
     let vt_matrix = svd_result.vt.ok_or_else(|| {
-        Error::new(magnus::exception::runtime_error(), "No V^T matrix in SVD result")
+        base_error("No V^T matrix in SVD result")
     })?;
     
     // Convert results to Ruby arrays
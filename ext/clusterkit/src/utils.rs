@@ -1,5 +1,6 @@
-use magnus::{function, prelude::*, Error, Value, RArray, TryConvert, Float, Integer};
+use magnus::{function, prelude::*, Error, Value, RArray, TryConvert};
 use ndarray::Array2;
+use crate::errors::{dimension_error, dimension_mismatch_error, invalid_input_error};
 
 pub fn init(parent: &magnus::RModule) -> Result<(), Error> {
     let utils_module = parent.define_module("Utils")?;
@@ -33,148 +34,101 @@ fn estimate_hubness(_data: Value) -> Result<Value, Error> {
     ))
 }
 
-/// Convert Ruby 2D array to ndarray Array2<f64>
-/// Handles validation and provides consistent error messages
-pub fn ruby_array_to_ndarray2(data: Value) -> Result<Array2<f64>, Error> {
-    let rarray: RArray = TryConvert::try_convert(data)?;
-    let n_samples = rarray.len();
+/// Numeric element types a Ruby matrix can be converted into
+pub trait MatrixElement: Copy {
+    fn from_f64(value: f64) -> Self;
+}
 
-    if n_samples == 0 {
-        return Err(Error::new(
-            magnus::exception::arg_error(),
-            "Data cannot be empty",
-        ));
+impl MatrixElement for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
     }
+}
 
-    // Get dimensions from first row
-    let first_row: RArray = rarray.entry::<RArray>(0)?;
-    let n_features = first_row.len();
-
-    if n_features == 0 {
-        return Err(Error::new(
-            magnus::exception::arg_error(),
-            "Data rows cannot be empty",
-        ));
+impl MatrixElement for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
     }
+}
 
-    // Create ndarray and populate
-    let mut data_array = Array2::<f64>::zeros((n_samples, n_features));
-    for i in 0..n_samples {
-        let row: RArray = rarray.entry(i as isize)?;
-        
-        // Validate row length consistency
-        if row.len() != n_features {
-            return Err(Error::new(
-                magnus::exception::arg_error(),
-                format!("Row {} has {} elements, expected {}", i, row.len(), n_features),
-            ));
-        }
+/// Convert Ruby 2D array to ndarray Array2<T>
+/// See `read_matrix` for the validation performed
+pub fn ruby_array_to_ndarray<T: MatrixElement>(data: Value) -> Result<Array2<T>, Error> {
+    let (values, n_samples, n_features) = read_matrix(data)?;
+    Ok(Array2::from_shape_vec((n_samples, n_features), values)
+        .expect("read_matrix returns n_samples * n_features values"))
+}
 
-        for j in 0..n_features {
-            let val: f64 = row.entry(j as isize)?;
-            data_array[[i, j]] = val;
+/// Convert Ruby 2D array to Vec<Vec<T>>
+/// See `read_matrix` for the validation performed
+pub fn ruby_array_to_matrix<T: MatrixElement>(data: Value) -> Result<Vec<Vec<T>>, Error> {
+    let (values, _, n_features) = read_matrix(data)?;
+    Ok(values.chunks(n_features).map(|row| row.to_vec()).collect())
+}
+
+/// Convert Ruby 1D array to Vec<T>, checking its length against `expected_dim` if given
+pub fn ruby_array_to_vector<T: MatrixElement>(array: RArray, expected_dim: Option<usize>) -> Result<Vec<T>, Error> {
+    let len = array.len();
+    if let Some(expected) = expected_dim {
+        if len != expected {
+            return Err(dimension_mismatch_error(format!(
+                "Vector dimension mismatch: expected {}, got {}", expected, len
+            )));
         }
     }
 
-    Ok(data_array)
+    let mut vec = Vec::with_capacity(len);
+    for j in 0..len {
+        let value = array.entry::<Value>(j as isize)?;
+        let val = f64::try_convert(value).map_err(|_| {
+            invalid_input_error(format!("Element at position [{}] is not numeric", j))
+        })?;
+        vec.push(T::from_f64(val));
+    }
+
+    Ok(vec)
 }
 
-/// Convert Ruby 2D array to Vec<Vec<f64>>
-/// Handles validation and provides consistent error messages  
-pub fn ruby_array_to_vec_vec_f64(data: Value) -> Result<Vec<Vec<f64>>, Error> {
-    let rarray: RArray = TryConvert::try_convert(data)?;
+/// Read a Ruby 2D array into a row-major buffer, returning (values, n_samples, n_features)
+/// Raises ClusterKit::InvalidInputError for empty, non-2D or non-numeric input and
+/// ClusterKit::DimensionError when rows have different lengths
+fn read_matrix<T: MatrixElement>(data: Value) -> Result<(Vec<T>, usize, usize), Error> {
+    let rarray = RArray::try_convert(data)
+        .map_err(|_| invalid_input_error("Data must be an array"))?;
     let n_samples = rarray.len();
 
     if n_samples == 0 {
-        return Err(Error::new(
-            magnus::exception::arg_error(),
-            "Data cannot be empty",
-        ));
+        return Err(invalid_input_error("Data cannot be empty"));
     }
 
-    let mut data_vec: Vec<Vec<f64>> = Vec::with_capacity(n_samples);
-    let mut expected_features: Option<usize> = None;
+    let mut values: Vec<T> = Vec::new();
+    let mut n_features = 0;
 
     for i in 0..n_samples {
-        let row: RArray = rarray.entry(i as isize)?;
-        let n_features = row.len();
-        
-        // Check row length consistency
-        match expected_features {
-            Some(expected) => {
-                if n_features != expected {
-                    return Err(Error::new(
-                        magnus::exception::arg_error(),
-                        format!("Row {} has {} elements, expected {}", i, n_features, expected),
-                    ));
-                }
+        let row = RArray::try_convert(rarray.entry::<Value>(i as isize)?)
+            .map_err(|_| invalid_input_error(format!("Row {} is not an array", i)))?;
+
+        // Dimensions come from the first row
+        if i == 0 {
+            n_features = row.len();
+            if n_features == 0 {
+                return Err(invalid_input_error("Data rows cannot be empty"));
             }
-            None => expected_features = Some(n_features),
+            values.reserve(n_samples * n_features);
+        } else if row.len() != n_features {
+            return Err(dimension_error(format!(
+                "Row {} has {} elements, expected {}", i, row.len(), n_features
+            )));
         }
 
-        let mut row_vec: Vec<f64> = Vec::with_capacity(n_features);
         for j in 0..n_features {
-            let val: f64 = row.entry(j as isize)?;
-            row_vec.push(val);
+            let value = row.entry::<Value>(j as isize)?;
+            let val = f64::try_convert(value).map_err(|_| {
+                invalid_input_error(format!("Element at position [{}, {}] is not numeric", i, j))
+            })?;
+            values.push(T::from_f64(val));
         }
-        data_vec.push(row_vec);
     }
 
-    Ok(data_vec)
+    Ok((values, n_samples, n_features))
 }
-
-/// Convert Ruby 2D array to Vec<Vec<f32>>
-/// For algorithms that require f32 precision (like UMAP)
-pub fn ruby_array_to_vec_vec_f32(data: Value) -> Result<Vec<Vec<f32>>, Error> {
-    let rarray: RArray = TryConvert::try_convert(data)?;
-    let array_len = rarray.len();
-
-    if array_len == 0 {
-        return Err(Error::new(
-            magnus::exception::arg_error(),
-            "Input data cannot be empty",
-        ));
-    }
-
-    let mut rust_data: Vec<Vec<f32>> = Vec::with_capacity(array_len);
-
-    for i in 0..array_len {
-        let row = rarray.entry::<Value>(i as isize)?;
-        let row_array = RArray::try_convert(row).map_err(|_| {
-            Error::new(
-                magnus::exception::type_error(),
-                "Expected array of arrays (2D array)",
-            )
-        })?;
-
-        let mut rust_row: Vec<f32> = Vec::new();
-        let row_len = row_array.len();
-
-        for j in 0..row_len {
-            let val = row_array.entry::<Value>(j as isize)?;
-            let float_val = if let Ok(f) = Float::try_convert(val) {
-                f.to_f64() as f32
-            } else if let Ok(i) = Integer::try_convert(val) {
-                i.to_i64()? as f32
-            } else {
-                return Err(Error::new(
-                    magnus::exception::type_error(),
-                    "All values must be numeric",
-                ));
-            };
-            rust_row.push(float_val);
-        }
-
-        // Validate row length consistency
-        if !rust_data.is_empty() && rust_row.len() != rust_data[0].len() {
-            return Err(Error::new(
-                magnus::exception::arg_error(),
-                "All rows must have the same length",
-            ));
-        }
-
-        rust_data.push(rust_row);
-    }
-
-    Ok(rust_data)
-}
\ No newline at end of file
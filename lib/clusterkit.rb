@@ -7,12 +7,11 @@ require_relative "clusterkit/configuration"
 # Main module for ClusterKit gem
 # Provides high-performance dimensionality reduction algorithms
 module ClusterKit
-  class Error < StandardError; end
-
+  # Error, InvalidInputError, DimensionError, DimensionMismatchError,
+  # ConvergenceError, NotFittedError and PersistenceError are defined by the
+  # native extension (ext/clusterkit/src/errors.rs) so Rust code can raise them.
 
   # Core error classes
-  class DimensionError < Error; end
-  class ConvergenceError < Error; end
   class InvalidParameterError < Error; end
   
   # Data-related errors
@@ -1,7 +1,14 @@
 # frozen_string_literal: true
 
 require_relative 'clusterkit'
+require_relative 'clustering/agglomerative'
+require_relative 'clustering/dbscan'
+require_relative 'clustering/gaussian_mixture'
 require_relative 'clustering/hdbscan'
+require_relative 'clustering/kmedoids'
+require_relative 'clustering/mini_batch_kmeans'
+require_relative 'clustering/optics'
+require_relative 'clustering/spectral'
 require_relative 'data_validator'
 
 module ClusterKit
@@ -9,28 +16,78 @@ module ClusterKit
   module Clustering
     # K-means clustering algorithm
     class KMeans
-      attr_reader :k, :max_iter, :centroids, :labels, :inertia
+      ALGORITHMS = [:lloyd, :elkan].freeze
+      EMPTY_CLUSTER_POLICIES = [:relocate, :keep].freeze
+      METRICS = [:euclidean, :cosine, :manhattan].freeze
+
+      attr_reader :k, :max_iter, :n_init, :tol, :algorithm, :empty_cluster, :metric,
+                  :centroids, :labels, :inertia, :n_iter, :n_relocations
 
       # Initialize K-means clusterer
       # @param k [Integer] Number of clusters
       # @param max_iter [Integer] Maximum iterations (default: 300)
       # @param random_seed [Integer] Random seed for reproducibility (optional)
-      def initialize(k:, max_iter: 300, random_seed: nil)
+      # @param n_init [Integer] Number of k-means++ initializations to run; the
+      #   lowest-inertia result is kept (default: 1)
+      # @param tol [Float] Convergence tolerance on the total squared centroid shift,
+      #   relative to the mean feature variance (default: 1e-4)
+      # @param algorithm [Symbol] :lloyd or :elkan. Elkan uses triangle-inequality
+      #   bounds to skip distance computations and gives the same result (default: :lloyd)
+      # @param empty_cluster [Symbol] What to do when a cluster loses all its points:
+      #   :relocate moves its centroid to the point farthest from its own centroid,
+      #   :keep leaves it in place (default: :relocate)
+      # @param metric [Symbol] :euclidean, :cosine (spherical k-means on unit-normalized
+      #   points and centroids) or :manhattan (L1 distance with median centroids)
+      #   (default: :euclidean)
+      def initialize(k:, max_iter: 300, random_seed: nil, n_init: 1, tol: 1e-4, algorithm: :lloyd,
+                     empty_cluster: :relocate, metric: :euclidean)
+        # Strings work as well as the documented Symbols
+        algorithm = algorithm.to_sym if algorithm.is_a?(String)
+        empty_cluster = empty_cluster.to_sym if empty_cluster.is_a?(String)
+        metric = metric.to_sym if metric.is_a?(String)
         raise ArgumentError, "k must be positive" unless k > 0
+        raise ArgumentError, "n_init must be positive" unless n_init.is_a?(Integer) && n_init > 0
+        raise ArgumentError, "tol must be non-negative" unless tol.is_a?(Numeric) && tol >= 0
+        unless ALGORITHMS.include?(algorithm)
+          raise ArgumentError, "algorithm must be one of: #{ALGORITHMS.join(', ')}"
+        end
+        unless EMPTY_CLUSTER_POLICIES.include?(empty_cluster)
+          raise ArgumentError, "empty_cluster must be one of: #{EMPTY_CLUSTER_POLICIES.join(', ')}"
+        end
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+        if algorithm == :elkan && metric == :manhattan
+          raise ArgumentError, "The elkan algorithm requires the euclidean or cosine metric"
+        end
         @k = k
         @max_iter = max_iter
         @random_seed = random_seed
+        @n_init = n_init
+        @tol = tol
+        @algorithm = algorithm
+        @empty_cluster = empty_cluster
+        @metric = metric
         @fitted = false
       end
 
       # Fit the K-means model
       # @param data [Array] 2D array of data points
+      # @param sample_weight [Array, nil] Non-negative weight per point, used in seeding,
+      #   centroid updates and inertia (optional)
       # @return [self] Returns self for method chaining
-      def fit(data)
+      def fit(data, sample_weight: nil)
         validate_data(data)
         
         # Call Rust implementation with optional seed
-        @labels, @centroids, @inertia = Clustering.kmeans_rust(data, @k, @max_iter, @random_seed)
+        result = Clustering.kmeans_rust(data, @k, @max_iter, @random_seed, @n_init, @tol.to_f,
+                                        @algorithm.to_s, @empty_cluster.to_s, sample_weight, @metric.to_s)
+        @labels = result["labels"]
+        @centroids = result["centroids"]
+        @inertia = result["inertia"]
+        @n_iter = result["n_iter"]
+        @converged = result["converged"]
+        @n_relocations = result["n_relocations"]
         @fitted = true
         
         self
@@ -40,17 +97,18 @@ module ClusterKit
       # @param data [Array] 2D array of data points
       # @return [Array] Cluster labels
       def predict(data)
-        raise RuntimeError, "Model must be fitted before predict" unless fitted?
+        raise NotFittedError, "Model must be fitted before predict" unless fitted?
         validate_data(data)
         
-        Clustering.kmeans_predict_rust(data, @centroids)
+        Clustering.kmeans_predict_rust(data, @centroids, @metric.to_s)
       end
 
       # Fit the model and return labels
       # @param data [Array] 2D array of data points
+      # @param sample_weight [Array, nil] Non-negative weight per point (optional)
       # @return [Array] Cluster labels
-      def fit_predict(data)
-        fit(data)
+      def fit_predict(data, sample_weight: nil)
+        fit(data, sample_weight: sample_weight)
         @labels
       end
 
@@ -60,6 +118,12 @@ module ClusterKit
         @fitted
       end
 
+      # Check if the best run converged before reaching max_iter
+      # @return [Boolean] True if labels stopped changing or centroids moved less than tol
+      def converged?
+        @converged == true
+      end
+
       # Get cluster centers
       # @return [Array] 2D array of cluster centers
       def cluster_centers
@@ -75,20 +139,39 @@ module ClusterKit
       # Class methods for K-means specific utilities
       class << self
         # Find optimal number of clusters using elbow method
+        # All k values are fitted in parallel natively
         # @param data [Array] 2D array of data points
         # @param k_range [Range] Range of k values to try
         # @param max_iter [Integer] Maximum iterations per k
+        # @param random_seed [Integer] Random seed for reproducibility (optional)
         # @return [Hash] Mapping of k to inertia values
-        def elbow_method(data, k_range: 2..10, max_iter: 300)
-          results = {}
-          
-          k_range.each do |k|
-            kmeans = new(k: k, max_iter: max_iter)
-            kmeans.fit(data)
-            results[k] = kmeans.inertia
-          end
-          
-          results
+        def elbow_method(data, k_range: 2..10, max_iter: 300, random_seed: nil)
+          DataValidator.validate_clustering(data, check_finite: false)
+
+          result = Clustering.kmeans_evaluate_k_rust(data, k_range.to_a, max_iter, random_seed, 1, 0, false)
+          result["k"].zip(result["inertia"]).to_h
+        end
+
+        # Evaluate a range of k with the elbow, gap statistic and silhouette criteria
+        # All k values (and gap reference datasets) are fitted in parallel natively
+        # @param data [Array] 2D array of data points
+        # @param k_range [Range] Range of k values to try (default: 2..10)
+        # @param max_iter [Integer] Maximum iterations per k (default: 300)
+        # @param n_init [Integer] k-means++ initializations per k (default: 1)
+        # @param n_references [Integer] Uniform reference datasets for the gap statistic,
+        #   drawn from the bounding box of the data; 0 skips it (default: 10)
+        # @param silhouette [Boolean] Compute the mean silhouette per k, which costs
+        #   O(n^2) per k (default: true)
+        # @param random_seed [Integer] Random seed for reproducibility (optional)
+        # @return [Hash] :k, :inertia, :gap, :gap_sd and :silhouette (arrays aligned with :k,
+        #   nil when skipped), plus :gap_k (smallest k with gap(k) >= gap(k+1) - sd(k+1))
+        #   and :silhouette_k (highest mean silhouette)
+        def evaluate_k(data, k_range: 2..10, max_iter: 300, n_init: 1, n_references: 10,
+                       silhouette: true, random_seed: nil)
+          DataValidator.validate_clustering(data, check_finite: false)
+
+          Clustering.kmeans_evaluate_k_rust(data, k_range.to_a, max_iter, random_seed, n_init,
+                                            n_references, silhouette).transform_keys(&:to_sym)
         end
 
         # Detect optimal k from elbow method results
@@ -120,10 +203,24 @@ module ClusterKit
         # @param data [Array] 2D array of data points
         # @param k_range [Range] Range of k values to try (default: 2..10)
         # @param max_iter [Integer] Maximum iterations (default: 300)
+        # @param criterion [Symbol] :elbow (largest inertia drop), :gap (gap statistic)
+        #   or :silhouette (highest mean silhouette) (default: :elbow)
+        # @param random_seed [Integer] Random seed for reproducibility (optional)
         # @return [Integer] Optimal number of clusters
-        def optimal_k(data, k_range: 2..10, max_iter: 300)
-          elbow_results = elbow_method(data, k_range: k_range, max_iter: max_iter)
-          detect_optimal_k(elbow_results)
+        def optimal_k(data, k_range: 2..10, max_iter: 300, criterion: :elbow, random_seed: nil)
+          case criterion
+          when :elbow
+            elbow_results = elbow_method(data, k_range: k_range, max_iter: max_iter, random_seed: random_seed)
+            detect_optimal_k(elbow_results)
+          when :gap
+            evaluate_k(data, k_range: k_range, max_iter: max_iter, silhouette: false,
+                       random_seed: random_seed)[:gap_k]
+          when :silhouette
+            evaluate_k(data, k_range: k_range, max_iter: max_iter, n_references: 0,
+                       random_seed: random_seed)[:silhouette_k]
+          else
+            raise ArgumentError, "criterion must be one of: elbow, gap, silhouette"
+          end
         end
       end
 
@@ -139,55 +236,98 @@ module ClusterKit
       # Calculate silhouette score for any clustering result
       # @param data [Array] 2D array of data points
       # @param labels [Array] Cluster labels
-      # @return [Float] Mean silhouette coefficient
-      def silhouette_score(data, labels)
-        n_samples = data.size
-        unique_labels = labels.uniq
-        
-        return 0.0 if unique_labels.size == 1
-        
-        silhouette_values = []
-        
-        data.each_with_index do |point, i|
-          cluster_label = labels[i]
-          
-          # Calculate mean intra-cluster distance
-          same_cluster_indices = labels.each_index.select { |j| labels[j] == cluster_label && j != i }
-          if same_cluster_indices.empty?
-            silhouette_values << 0.0
-            next
-          end
-          
-          a = same_cluster_indices.sum { |j| euclidean_distance(point, data[j]) } / same_cluster_indices.size.to_f
-          
-          # Calculate mean nearest-cluster distance
-          b = Float::INFINITY
-          unique_labels.each do |other_label|
-            next if other_label == cluster_label
-            
-            other_cluster_indices = labels.each_index.select { |j| labels[j] == other_label }
-            next if other_cluster_indices.empty?
-            
-            mean_dist = other_cluster_indices.sum { |j| euclidean_distance(point, data[j]) } / other_cluster_indices.size.to_f
-            b = mean_dist if mean_dist < b
-          end
-          
-          # Calculate silhouette value for this point
-          if a == 0.0 && b == 0.0
-            s = 0.0  # When all points are identical
-          else
-            s = (b - a) / [a, b].max
-          end
-          silhouette_values << s
-        end
-        
-        silhouette_values.sum / silhouette_values.size.to_f
+      # @param sample_size [Integer, nil] Score a random subset of this many points
+      #   instead of all of them, for large datasets (optional)
+      # @param random_seed [Integer, nil] Random seed for the subset (optional)
+      # @param exclude_noise [Boolean] Ignore points labeled -1 (HDBSCAN noise) (default: true)
+      # @return [Float] Mean silhouette coefficient (0.0 with fewer than two clusters)
+      def silhouette_score(data, labels, sample_size: nil, random_seed: nil, exclude_noise: true)
+        silhouette_score_rust(data, labels, exclude_noise, sample_size, random_seed)
       end
 
-      private
+      # Calculate the Davies-Bouldin index (lower is better)
+      # @param data [Array] 2D array of data points
+      # @param labels [Array] Cluster labels
+      # @param exclude_noise [Boolean] Ignore points labeled -1 (HDBSCAN noise) (default: true)
+      # @return [Float] Mean worst-case ratio of cluster scatter to centroid separation
+      def davies_bouldin_score(data, labels, exclude_noise: true)
+        davies_bouldin_score_rust(data, labels, exclude_noise)
+      end
+
+      # Calculate the Calinski-Harabasz index (higher is better)
+      # @param data [Array] 2D array of data points
+      # @param labels [Array] Cluster labels
+      # @param exclude_noise [Boolean] Ignore points labeled -1 (HDBSCAN noise) (default: true)
+      # @return [Float] Ratio of between-cluster to within-cluster dispersion
+      def calinski_harabasz_score(data, labels, exclude_noise: true)
+        calinski_harabasz_score_rust(data, labels, exclude_noise)
+      end
+
+      # Contingency matrix between two labelings
+      # @param labels_true [Array] Reference labels (rows)
+      # @param labels_pred [Array] Predicted labels (columns)
+      # @param noise [Symbol] How points labeled -1 are handled: :exclude drops points that
+      #   are noise in either labeling, :cluster treats noise as one cluster, :singletons
+      #   treats each noise point as its own cluster (default: :exclude)
+      # @return [Hash] "matrix" (counts), "true_labels" and "pred_labels" (row and column labels)
+      def contingency_matrix(labels_true, labels_pred, noise: :exclude)
+        contingency_matrix_rust(labels_true, labels_pred, noise.to_s)
+      end
+
+      # Rand index adjusted for chance (1.0 for identical partitions, ~0.0 for random ones)
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Float] Adjusted Rand index
+      def adjusted_rand_score(labels_true, labels_pred, noise: :exclude)
+        adjusted_rand_score_rust(labels_true, labels_pred, noise.to_s)
+      end
+
+      # Mutual information normalized by the arithmetic mean of the label entropies
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Float] Normalized mutual information in [0, 1]
+      def normalized_mutual_info_score(labels_true, labels_pred, noise: :exclude)
+        normalized_mutual_info_score_rust(labels_true, labels_pred, noise.to_s)
+      end
+
+      # Mutual information adjusted for chance
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Float] Adjusted mutual information (1.0 for identical partitions, ~0.0 for random ones)
+      def adjusted_mutual_info_score(labels_true, labels_pred, noise: :exclude)
+        adjusted_mutual_info_score_rust(labels_true, labels_pred, noise.to_s)
+      end
+
+      # Homogeneity, completeness and V-measure
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param beta [Float] Weight of completeness relative to homogeneity (default: 1.0)
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Array<Float>] [homogeneity, completeness, v_measure]
+      def homogeneity_completeness_v_measure(labels_true, labels_pred, beta: 1.0, noise: :exclude)
+        homogeneity_completeness_v_measure_rust(labels_true, labels_pred, noise.to_s, beta.to_f)
+      end
+
+      # V-measure, the weighted harmonic mean of homogeneity and completeness
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param beta [Float] Weight of completeness relative to homogeneity (default: 1.0)
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Float] V-measure in [0, 1]
+      def v_measure_score(labels_true, labels_pred, beta: 1.0, noise: :exclude)
+        homogeneity_completeness_v_measure(labels_true, labels_pred, beta: beta, noise: noise)[2]
+      end
 
-      def euclidean_distance(a, b)
-        Math.sqrt(a.zip(b).sum { |x, y| (x - y) ** 2 })
+      # Fowlkes-Mallows index, the geometric mean of pairwise precision and recall
+      # @param labels_true [Array] Reference labels
+      # @param labels_pred [Array] Predicted labels
+      # @param noise [Symbol] Noise handling, see #contingency_matrix (default: :exclude)
+      # @return [Float] Fowlkes-Mallows index in [0, 1]
+      def fowlkes_mallows_score(labels_true, labels_pred, noise: :exclude)
+        fowlkes_mallows_score_rust(labels_true, labels_pred, noise.to_s)
       end
     end
   end
@@ -0,0 +1,123 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # Agglomerative (bottom-up hierarchical) clustering - matching KMeans API pattern
+    #
+    # Starts from one cluster per point and repeatedly merges the two closest clusters,
+    # where the distance between clusters depends on the linkage:
+    # - 'single': closest pair of points (computed from the minimum spanning tree)
+    # - 'complete': farthest pair of points
+    # - 'average': mean distance over all pairs of points
+    # - 'ward': increase in within-cluster variance (euclidean only)
+    #
+    # The full merge tree is returned as linkage_matrix, in SciPy linkage format: row i is
+    # [left, right, distance, size], merging nodes left and right (points are numbered
+    # from 0, the node formed on row i is n_samples + i), ready to draw as a dendrogram.
+    # Complete, average and Ward linkage keep an n x n distance matrix in memory.
+    class AgglomerativeClustering
+      METRICS = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine', 'chebyshev', 'precomputed'].freeze
+      LINKAGES = ['single', 'complete', 'average', 'ward'].freeze
+
+      attr_reader :n_clusters, :distance_threshold, :linkage, :metric,
+                  :labels, :linkage_matrix, :n_clusters_found
+
+      # Initialize agglomerative clusterer (matches KMeans pattern)
+      # @param n_clusters [Integer, nil] Number of clusters to cut the tree into; nil with a
+      #   distance_threshold (default: 2 when no distance_threshold is given)
+      # @param distance_threshold [Float, nil] Cut the tree instead at this distance: merges at or
+      #   above it are undone (default: nil)
+      # @param linkage [String] 'single', 'complete', 'average' or 'ward' (default: 'ward')
+      # @param metric [String] Distance metric: 'euclidean' ('l2'), 'manhattan' ('l1'), 'cosine',
+      #   'chebyshev' or 'precomputed', in which case fit expects an n x n distance matrix
+      #   (default: 'euclidean')
+      def initialize(n_clusters: nil, distance_threshold: nil, linkage: 'ward', metric: 'euclidean')
+        # Symbols work as well as the documented Strings
+        linkage = linkage.to_s if linkage.is_a?(Symbol)
+        metric = metric.to_s if metric.is_a?(Symbol)
+        if n_clusters && distance_threshold
+          raise ArgumentError, "Give either n_clusters or distance_threshold, not both"
+        end
+        n_clusters = 2 if n_clusters.nil? && distance_threshold.nil?
+
+        raise ArgumentError, "n_clusters must be positive" if n_clusters && n_clusters <= 0
+        raise ArgumentError, "distance_threshold must be non-negative" if distance_threshold && distance_threshold < 0
+
+        unless LINKAGES.include?(linkage)
+          raise ArgumentError, "linkage must be one of: #{LINKAGES.join(', ')}"
+        end
+
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+
+        if linkage == 'ward' && !['euclidean', 'l2'].include?(metric)
+          raise ArgumentError, "ward linkage requires the euclidean metric"
+        end
+
+        @n_clusters = n_clusters
+        @distance_threshold = distance_threshold&.to_f
+        @linkage = linkage
+        @metric = metric
+        @fitted = false
+      end
+
+      # Fit the hierarchy and cut it (matches KMeans.fit)
+      # @param data [Array] 2D array of data points, or a distance matrix for 'precomputed'
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        DataValidator.validate_clustering(data, check_finite: false)
+
+        result = Clustering.agglomerative_rust(data, @n_clusters, @distance_threshold, @linkage, @metric)
+
+        @labels = result["labels"]
+        @linkage_matrix = result["linkage_matrix"]
+        @n_clusters_found = result["n_clusters"]
+        @fitted = true
+
+        self
+      end
+
+      # Fit the model and return labels (matches KMeans.fit_predict)
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels, numbered in order of each cluster's first point
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted (matches KMeans.fitted?)
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+    end
+
+    # Module-level convenience methods (matching KMeans pattern)
+    class << self
+      # Perform agglomerative clustering
+      # @param data [Array] 2D array of data points
+      # @param n_clusters [Integer, nil] Number of clusters (2 when no distance_threshold is given)
+      # @param distance_threshold [Float, nil] Cut distance instead of n_clusters
+      # @param linkage [String] 'single', 'complete', 'average' or 'ward'
+      # @param metric [String] Distance metric
+      # @return [Hash] Result hash with :labels, :n_clusters and :linkage_matrix
+      def agglomerative(data, n_clusters: nil, distance_threshold: nil, linkage: 'ward', metric: 'euclidean')
+        clusterer = AgglomerativeClustering.new(
+          n_clusters: n_clusters,
+          distance_threshold: distance_threshold,
+          linkage: linkage,
+          metric: metric
+        )
+        clusterer.fit(data)
+        {
+          labels: clusterer.labels,
+          n_clusters: clusterer.n_clusters_found,
+          linkage_matrix: clusterer.linkage_matrix
+        }
+      end
+    end
+  end
+end
@@ -0,0 +1,105 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # DBSCAN clustering algorithm - matching KMeans API pattern
+    #
+    # Points with at least min_samples neighbors (themselves included) within eps are
+    # core points. Clusters are the groups of core points reachable from one another
+    # through such neighborhoods, together with the border points they reach; every
+    # other point is noise (-1). Neighborhoods come from an exact KD-tree search.
+    class DBSCAN
+      METRICS = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine', 'chebyshev', 'precomputed'].freeze
+
+      attr_reader :eps, :min_samples, :metric, :labels, :core_sample_indices
+
+      # Initialize DBSCAN clusterer (matches KMeans pattern)
+      # @param eps [Float] Neighborhood radius (default: 0.5)
+      # @param min_samples [Integer] Neighbors within eps, the point included, that make a
+      #   core point (default: 5)
+      # @param metric [String] Distance metric: 'euclidean' ('l2'), 'manhattan' ('l1'), 'cosine',
+      #   'chebyshev' or 'precomputed', in which case fit expects an n x n distance matrix
+      #   (default: 'euclidean')
+      def initialize(eps: 0.5, min_samples: 5, metric: 'euclidean')
+        # Symbols work as well as the documented Strings
+        metric = metric.to_s if metric.is_a?(Symbol)
+        raise ArgumentError, "eps must be positive" unless eps > 0 && eps.to_f.finite?
+        raise ArgumentError, "min_samples must be positive" unless min_samples > 0
+
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+
+        @eps = eps.to_f
+        @min_samples = min_samples
+        @metric = metric
+        @fitted = false
+      end
+
+      # Fit the DBSCAN model (matches KMeans.fit)
+      # @param data [Array] 2D array of data points, or a distance matrix for 'precomputed'
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        DataValidator.validate_clustering(data, check_finite: false)
+
+        result = Clustering.dbscan_rust(data, @eps, @min_samples, @metric)
+
+        @labels = result["labels"]
+        @core_sample_indices = result["core_sample_indices"]
+        @fitted = true
+
+        self
+      end
+
+      # Fit the model and return labels (matches KMeans.fit_predict)
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels (-1 for noise)
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted (matches KMeans.fitted?)
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Get number of clusters found
+      # @return [Integer] Number of clusters (excluding noise)
+      def n_clusters
+        return 0 unless fitted?
+        @labels.uniq.reject { |l| l == -1 }.length
+      end
+
+      # Get the number of noise points
+      # @return [Integer] Number of points labeled as noise
+      def n_noise_points
+        return 0 unless fitted?
+        @labels.count(-1)
+      end
+    end
+
+    # Module-level convenience methods (matching KMeans pattern)
+    class << self
+      # Perform DBSCAN clustering
+      # @param data [Array] 2D array of data points
+      # @param eps [Float] Neighborhood radius
+      # @param min_samples [Integer] Neighbors within eps that make a core point
+      # @param metric [String] Distance metric
+      # @return [Hash] Result hash with :labels, :core_sample_indices, :n_clusters and :n_noise_points
+      def dbscan(data, eps: 0.5, min_samples: 5, metric: 'euclidean')
+        clusterer = DBSCAN.new(eps: eps, min_samples: min_samples, metric: metric)
+        clusterer.fit(data)
+        {
+          labels: clusterer.labels,
+          core_sample_indices: clusterer.core_sample_indices,
+          n_clusters: clusterer.n_clusters,
+          n_noise_points: clusterer.n_noise_points
+        }
+      end
+    end
+  end
+end
@@ -0,0 +1,183 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # Gaussian mixture model - matching KMeans API pattern
+    #
+    # Models the data as a weighted sum of Gaussians fitted by expectation-maximization,
+    # giving soft assignments (predict_proba) and clusters with their own shape:
+    # - :full - any ellipsoid (a d x d covariance matrix per component)
+    # - :diag - axis-aligned ellipsoids (d variances per component)
+    # - :spherical - spheres of different sizes (one variance per component)
+    # Each run starts from k-means++ seeds; compare models with bic / aic (lower is better).
+    class GaussianMixture
+      COVARIANCE_TYPES = [:full, :diag, :spherical].freeze
+
+      attr_reader :n_components, :covariance_type, :tol, :reg_covar, :max_iter, :n_init,
+                  :labels, :weights, :means, :covariances, :n_iter, :lower_bound
+
+      # Initialize Gaussian mixture
+      # @param n_components [Integer] Number of mixture components
+      # @param covariance_type [Symbol] :full, :diag or :spherical (default: :full)
+      # @param tol [Float] Stop once the mean log-likelihood improves by less than this
+      #   (default: 1e-3)
+      # @param reg_covar [Float] Added to every variance to keep covariances positive definite
+      #   (default: 1e-6)
+      # @param max_iter [Integer] Maximum EM iterations per run (default: 100)
+      # @param n_init [Integer] Number of initializations; the most likely fit is kept (default: 1)
+      # @param random_seed [Integer] Random seed for k-means++ seeding (optional)
+      def initialize(n_components: 1, covariance_type: :full, tol: 1e-3, reg_covar: 1e-6, max_iter: 100,
+                     n_init: 1, random_seed: nil)
+        # Strings work as well as the documented Symbols
+        covariance_type = covariance_type.to_sym if covariance_type.is_a?(String)
+        raise ArgumentError, "n_components must be positive" unless n_components > 0
+        raise ArgumentError, "tol must be non-negative" if tol < 0
+        raise ArgumentError, "reg_covar must be non-negative" if reg_covar < 0
+        raise ArgumentError, "max_iter must be positive" unless max_iter > 0
+        raise ArgumentError, "n_init must be positive" unless n_init > 0
+        unless COVARIANCE_TYPES.include?(covariance_type)
+          raise ArgumentError, "covariance_type must be one of: #{COVARIANCE_TYPES.join(', ')}"
+        end
+        @n_components = n_components
+        @covariance_type = covariance_type
+        @tol = tol.to_f
+        @reg_covar = reg_covar.to_f
+        @max_iter = max_iter
+        @n_init = n_init
+        @random_seed = random_seed
+        @fitted = false
+      end
+
+      # Fit the mixture
+      # @param data [Array] 2D array of data points
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        validate_data(data)
+
+        result = Clustering.gmm_rust(data, @n_components, @covariance_type.to_s, @tol, @reg_covar,
+                                     @max_iter, @n_init, @random_seed)
+        @labels = result["labels"]
+        @weights = result["weights"]
+        @means = result["means"]
+        @covariances = result["covariances"]
+        @converged = result["converged"]
+        @n_iter = result["n_iter"]
+        @lower_bound = result["lower_bound"]
+        @model = result["model"]
+        @fitted = true
+
+        if !@converged && ::ClusterKit.configuration.verbose
+          warn "GaussianMixture: EM did not converge in #{@max_iter} iterations; " \
+               "try a larger max_iter or tol"
+        end
+
+        self
+      end
+
+      # Most likely component of each point
+      # @param data [Array] 2D array of data points
+      # @return [Array] Component labels
+      def predict(data)
+        check_fitted_and_validate(data)
+        @model.predict(data)
+      end
+
+      # Posterior probability of each component for each point
+      # @param data [Array] 2D array of data points
+      # @return [Array<Array<Float>>] One row of n_components probabilities per point
+      def predict_proba(data)
+        check_fitted_and_validate(data)
+        @model.predict_proba(data)
+      end
+
+      # Log-likelihood of each point under the mixture
+      # @param data [Array] 2D array of data points
+      # @return [Array<Float>] Log density per point
+      def score_samples(data)
+        check_fitted_and_validate(data)
+        @model.score_samples(data)
+      end
+
+      # Mean log-likelihood of the data
+      # @param data [Array] 2D array of data points
+      # @return [Float]
+      def score(data)
+        scores = score_samples(data)
+        scores.sum / scores.size
+      end
+
+      # Bayesian information criterion; lower is better
+      # @param data [Array] 2D array of data points
+      # @return [Float]
+      def bic(data)
+        check_fitted_and_validate(data)
+        @model.bic(data)
+      end
+
+      # Akaike information criterion; lower is better
+      # @param data [Array] 2D array of data points
+      # @return [Float]
+      def aic(data)
+        check_fitted_and_validate(data)
+        @model.aic(data)
+      end
+
+      # Fit the model and return labels
+      # @param data [Array] 2D array of data points
+      # @return [Array] Component labels
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Whether the best run converged before max_iter
+      # @return [Boolean]
+      def converged?
+        @converged ? true : false
+      end
+
+      private
+
+      def check_fitted_and_validate(data)
+        raise NotFittedError, "Model must be fitted before predict" unless fitted?
+        validate_data(data)
+      end
+
+      def validate_data(data)
+        DataValidator.validate_clustering(data, check_finite: false)
+      end
+    end
+
+    # Module-level convenience methods (matching KMeans pattern)
+    class << self
+      # Fit a Gaussian mixture model
+      # @param data [Array] 2D array of data points
+      # @param n_components [Integer] Number of mixture components
+      # @param covariance_type [Symbol] :full, :diag or :spherical
+      # @param options [Hash] Further options (tol, reg_covar, max_iter, n_init, random_seed)
+      # @return [Hash] Result hash with :labels, :probabilities, :weights, :means, :covariances,
+      #   :bic and :aic
+      def gmm(data, n_components:, covariance_type: :full, **options)
+        mixture = GaussianMixture.new(n_components: n_components, covariance_type: covariance_type, **options)
+        mixture.fit(data)
+        {
+          labels: mixture.labels,
+          probabilities: mixture.predict_proba(data),
+          weights: mixture.weights,
+          means: mixture.means,
+          covariances: mixture.covariances,
+          bic: mixture.bic(data),
+          aic: mixture.aic(data)
+        }
+      end
+    end
+  end
+end
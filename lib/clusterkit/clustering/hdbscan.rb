@@ -5,54 +5,163 @@ require_relative '../data_validator'
 module ClusterKit
   module Clustering
     # HDBSCAN clustering algorithm - matching KMeans API pattern
+    #
+    # After fitting, the hierarchy is available for inspection:
+    # - cluster_persistence: label => persistence in [0, 1] (higher is more trustworthy)
+    # - cluster_stability: label => raw excess-of-mass stability
+    # - condensed_tree: rows of [parent, child, lambda_val, child_size]; children below
+    #   the number of samples are points, the root cluster is numbered n_samples
+    # - single_linkage_tree: SciPy-style linkage rows of [left, right, distance, size]
+    #   over the mutual reachability distance
+    #
+    # Each cluster is also summarized by representative points, in label order:
+    # - centroids: mean of the members weighted by membership probability (on unit-length
+    #   rows for 'cosine'; nil for 'precomputed')
+    # - medoids / medoid_indices: the member closest to the rest of its cluster
+    # - exemplars / exemplar_indices: the most persistent members, those that stay in the
+    #   cluster's densest parts the longest
+    # For 'precomputed' the points are rows of the distance matrix, so only the indices are set
     class HDBSCAN
-      attr_reader :min_samples, :min_cluster_size, :metric, :labels, :probabilities, 
-                  :outlier_scores, :cluster_persistence
+      METRICS = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine', 'chebyshev', 'precomputed'].freeze
+      CLUSTER_SELECTION_METHODS = ['eom', 'leaf'].freeze
+      ALGORITHMS = ['exact', 'hnsw'].freeze
+
+      attr_reader :min_samples, :min_cluster_size, :metric, :cluster_selection_epsilon,
+                  :cluster_selection_method, :allow_single_cluster, :max_cluster_size, :alpha,
+                  :algorithm, :n_neighbors, :random_seed,
+                  :labels, :probabilities, :outlier_scores, :cluster_persistence, :cluster_stability,
+                  :condensed_tree, :single_linkage_tree, :adjusted_parameters,
+                  :centroids, :medoids, :medoid_indices, :exemplars, :exemplar_indices
 
       # Initialize HDBSCAN clusterer (matches KMeans pattern)
       # @param min_samples [Integer] Min neighborhood size for core points (default: 5)
       # @param min_cluster_size [Integer] Minimum size of clusters (default: 5)
-      # @param metric [String] Distance metric (default: 'euclidean')
-      def initialize(min_samples: 5, min_cluster_size: 5, metric: 'euclidean')
+      # @param metric [String] Distance metric: 'euclidean' ('l2'), 'manhattan' ('l1'), 'cosine',
+      #   'chebyshev' or 'precomputed', in which case fit expects an n x n distance matrix
+      #   (default: 'euclidean')
+      # @param cluster_selection_epsilon [Float] Merge clusters that split off below this distance
+      #   into their ancestors; 0.0 disables merging (default: 0.0)
+      # @param cluster_selection_method [String] 'eom' (excess of mass, favors a few stable clusters)
+      #   or 'leaf' (the leaves of the condensed tree, many small homogeneous clusters) (default: 'eom')
+      # @param allow_single_cluster [Boolean] Allow the whole dataset to be returned as one cluster
+      #   (default: false)
+      # @param max_cluster_size [Integer, nil] Largest cluster 'eom' may select, nil for no limit
+      #   (default: nil)
+      # @param alpha [Float] Distance scaling in the mutual reachability; values above 1.0 are
+      #   more conservative (default: 1.0)
+      # @param algorithm [String] 'exact' computes all pairwise distances (O(n²)); 'hnsw' takes core
+      #   distances and the spanning tree from an approximate k-nearest-neighbor graph built with
+      #   HNSW, for large datasets (default: 'exact')
+      # @param n_neighbors [Integer] Neighbors per point in the 'hnsw' graph, at least min_samples
+      #   (default: 15)
+      # @param random_seed [Integer, nil] Seed for a reproducible 'hnsw' graph (default: nil)
+      def initialize(min_samples: 5, min_cluster_size: 5, metric: 'euclidean',
+                     cluster_selection_epsilon: 0.0, cluster_selection_method: 'eom',
+                     allow_single_cluster: false, max_cluster_size: nil, alpha: 1.0,
+                     algorithm: 'exact', n_neighbors: 15, random_seed: nil)
+        # Symbols work as well as the documented Strings
+        metric = metric.to_s if metric.is_a?(Symbol)
+        cluster_selection_method = cluster_selection_method.to_s if cluster_selection_method.is_a?(Symbol)
+        algorithm = algorithm.to_s if algorithm.is_a?(Symbol)
         raise ArgumentError, "min_samples must be positive" unless min_samples > 0
         raise ArgumentError, "min_cluster_size must be positive" unless min_cluster_size > 0
+        raise ArgumentError, "cluster_selection_epsilon must be non-negative" unless cluster_selection_epsilon >= 0
+        raise ArgumentError, "alpha must be positive" unless alpha > 0
+        raise ArgumentError, "n_neighbors must be positive" unless n_neighbors > 0
+        
+        unless max_cluster_size.nil? || max_cluster_size > 0
+          raise ArgumentError, "max_cluster_size must be positive or nil"
+        end
+        
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
         
-        valid_metrics = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine']
-        unless valid_metrics.include?(metric)
-          raise ArgumentError, "metric must be one of: #{valid_metrics.join(', ')}"
+        unless CLUSTER_SELECTION_METHODS.include?(cluster_selection_method)
+          raise ArgumentError, "cluster_selection_method must be one of: #{CLUSTER_SELECTION_METHODS.join(', ')}"
+        end
+        
+        unless ALGORITHMS.include?(algorithm)
+          raise ArgumentError, "algorithm must be one of: #{ALGORITHMS.join(', ')}"
+        end
+        
+        if algorithm == 'hnsw' && metric == 'precomputed'
+          raise ArgumentError, "algorithm 'hnsw' needs data points, not a precomputed distance matrix"
         end
         
         @min_samples = min_samples
         @min_cluster_size = min_cluster_size
         @metric = metric
+        @cluster_selection_epsilon = cluster_selection_epsilon.to_f
+        @cluster_selection_method = cluster_selection_method
+        @allow_single_cluster = allow_single_cluster ? true : false
+        @max_cluster_size = max_cluster_size
+        @alpha = alpha.to_f
+        @algorithm = algorithm
+        @n_neighbors = n_neighbors
+        @random_seed = random_seed
         @fitted = false
       end
 
       # Fit the HDBSCAN model (matches KMeans.fit)
-      # @param data [Array] 2D array of data points
+      # @param data [Array] 2D array of data points, or a distance matrix for 'precomputed'
       # @return [self] Returns self for method chaining
       def fit(data)
         validate_data(data)
         
-        # Call Rust implementation (hdbscan crate)
-        result = Clustering.hdbscan_rust(data, @min_samples, @min_cluster_size, @metric)
+        # Call native Rust implementation
+        result = Clustering.hdbscan_rust(
+          data, @min_samples, @min_cluster_size, @metric, @cluster_selection_epsilon,
+          @cluster_selection_method, @allow_single_cluster, @max_cluster_size, @alpha,
+          @algorithm, @n_neighbors, @random_seed
+        )
+        
+        # Parameters the data could not support, as name => [requested, used]
+        @adjusted_parameters = result["adjusted_parameters"].transform_keys(&:to_sym)
+        if ::ClusterKit.configuration.verbose
+          @adjusted_parameters.each do |name, (requested, used)|
+            warn "HDBSCAN: Adjusted #{name} from #{requested} to #{used} for dataset with #{data.size} samples"
+          end
+        end
         
         @labels = result["labels"]
         @probabilities = result["probabilities"]
         @outlier_scores = result["outlier_scores"]
         @cluster_persistence = result["cluster_persistence"]
+        @cluster_stability = result["cluster_stability"]
+        @condensed_tree = result["condensed_tree"]
+        @single_linkage_tree = result["single_linkage_tree"]
+        @centroids = result["centroids"]
+        @medoid_indices = result["medoid_indices"]
+        @exemplar_indices = result["exemplar_indices"]
+        unless @metric == 'precomputed'
+          @medoids = @medoid_indices.map { |index| data[index] }
+          @exemplars = @exemplar_indices.map { |indices| indices.map { |index| data[index] } }
+        end
+        @model = result["model"]
         @fitted = true
         
         self
       end
 
-      # HDBSCAN doesn't support predict for new points (unlike KMeans)
-      # But we keep the method for API consistency
-      # @param data [Array] 2D array of data points
-      # @return [Array] Returns nil or raises
+      # Assign new points to the fitted clusters without refitting (matches KMeans.predict)
+      # @param data [Array] 2D array of data points, or distances to the training points for 'precomputed'
+      # @return [Array] Cluster labels (-1 for noise)
       def predict(data)
-        raise NotImplementedError, "HDBSCAN does not support prediction on new data. " \
-                                  "Use approximate_predict for approximate membership"
+        approximate_predict(data).first
+      end
+
+      # Place new points in the fitted cluster hierarchy without changing it
+      # Each point joins the tree next to its nearest training point under mutual
+      # reachability and takes the label of the cluster it lands in
+      # @param data [Array] 2D array of data points, or distances to the training points for 'precomputed'
+      # @return [Array] Labels (-1 for noise) and membership probabilities, as [labels, probabilities]
+      def approximate_predict(data)
+        raise NotFittedError, "Model must be fitted before predict" unless fitted?
+        validate_data(data)
+
+        result = @model.approximate_predict(data)
+        [result["labels"], result["probabilities"]]
       end
 
       # Fit the model and return labels (matches KMeans.fit_predict)
@@ -142,12 +251,18 @@ module ClusterKit
       # @param min_samples [Integer] Min neighborhood size for core points
       # @param min_cluster_size [Integer] Minimum size of clusters
       # @param metric [String] Distance metric
-      # @return [Hash] Result hash with :labels, :probabilities, :outlier_scores
-      def hdbscan(data, min_samples: 5, min_cluster_size: 5, metric: 'euclidean')
+      # @param options [Hash] Further HDBSCAN options (cluster_selection_epsilon, cluster_selection_method,
+      #   allow_single_cluster, max_cluster_size, alpha, algorithm, n_neighbors, random_seed)
+      # @return [Hash] Result hash with :labels, :probabilities (membership strength in [0, 1]),
+      #   :outlier_scores (GLOSH, higher is more anomalous), :cluster_persistence, :cluster_stability,
+      #   :condensed_tree, :single_linkage_tree, :centroids, :medoids, :exemplars and
+      #   :adjusted_parameters (name => [requested, used])
+      def hdbscan(data, min_samples: 5, min_cluster_size: 5, metric: 'euclidean', **options)
         clusterer = HDBSCAN.new(
           min_samples: min_samples,
           min_cluster_size: min_cluster_size,
-          metric: metric
+          metric: metric,
+          **options
         )
         clusterer.fit(data)
         {
@@ -156,7 +271,14 @@ module ClusterKit
           outlier_scores: clusterer.outlier_scores,
           n_clusters: clusterer.n_clusters,
           noise_ratio: clusterer.noise_ratio,
-          cluster_persistence: clusterer.cluster_persistence || {}
+          cluster_persistence: clusterer.cluster_persistence || {},
+          cluster_stability: clusterer.cluster_stability || {},
+          condensed_tree: clusterer.condensed_tree,
+          single_linkage_tree: clusterer.single_linkage_tree,
+          centroids: clusterer.centroids,
+          medoids: clusterer.medoids,
+          exemplars: clusterer.exemplars,
+          adjusted_parameters: clusterer.adjusted_parameters
         }
       end
     end
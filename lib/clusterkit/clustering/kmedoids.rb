@@ -0,0 +1,106 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # K-medoids clustering - matching KMeans API pattern
+    #
+    # Cluster representatives (medoids) are actual rows of the input, and any
+    # of the supported distances (or a precomputed distance matrix) can be used.
+    class KMedoids
+      METRICS = [:euclidean, :manhattan, :cosine, :precomputed].freeze
+      ALGORITHMS = [:pam, :clara].freeze
+
+      attr_reader :k, :metric, :algorithm, :max_iter, :sample_size,
+                  :medoid_indices, :medoids, :labels, :cost, :n_iter
+
+      # Initialize K-medoids clusterer
+      # @param k [Integer] Number of clusters
+      # @param metric [Symbol] :euclidean, :manhattan, :cosine or :precomputed, in which
+      #   case fit expects an n x n distance matrix (default: :euclidean)
+      # @param algorithm [Symbol] :pam runs BUILD and FastPAM1 swaps on all points; :clara
+      #   runs PAM on random samples and suits large datasets (default: :pam)
+      # @param max_iter [Integer] Maximum number of swaps (default: 300)
+      # @param random_seed [Integer] Random seed for CLARA sampling (optional)
+      # @param sample_size [Integer] Points per CLARA sample (default: 40 + 2k)
+      def initialize(k:, metric: :euclidean, algorithm: :pam, max_iter: 300, random_seed: nil,
+                     sample_size: nil)
+        # Strings work as well as the documented Symbols
+        metric = metric.to_sym if metric.is_a?(String)
+        algorithm = algorithm.to_sym if algorithm.is_a?(String)
+        raise ArgumentError, "k must be positive" unless k > 0
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+        unless ALGORITHMS.include?(algorithm)
+          raise ArgumentError, "algorithm must be one of: #{ALGORITHMS.join(', ')}"
+        end
+        @k = k
+        @metric = metric
+        @algorithm = algorithm
+        @max_iter = max_iter
+        @random_seed = random_seed
+        @sample_size = sample_size
+        @fitted = false
+      end
+
+      # Fit the K-medoids model
+      # @param data [Array] 2D array of data points, or a distance matrix for :precomputed
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        validate_data(data)
+
+        result = Clustering.kmedoids_rust(data, @k, @metric.to_s, @algorithm.to_s, @max_iter,
+                                          @random_seed, @sample_size)
+        @medoid_indices = result["medoid_indices"]
+        @labels = result["labels"]
+        @cost = result["cost"]
+        @n_iter = result["n_iter"]
+        @medoids = @metric == :precomputed ? nil : @medoid_indices.map { |i| data[i] }
+        @fitted = true
+
+        self
+      end
+
+      # Predict cluster labels for new data
+      # @param data [Array] 2D array of data points
+      # @return [Array] Index of the nearest medoid for each point
+      def predict(data)
+        raise NotFittedError, "Model must be fitted before predict" unless fitted?
+        raise ArgumentError, "predict is not available with a precomputed metric" if @metric == :precomputed
+        validate_data(data)
+
+        # Nearest-medoid ranking matches nearest-centroid ranking under the same metric
+        Clustering.kmeans_predict_rust(data, @medoids, @metric.to_s)
+      end
+
+      # Fit the model and return labels
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Get cluster centers
+      # @return [Array] 2D array of medoids (rows of the training data)
+      def cluster_centers
+        @medoids
+      end
+
+      private
+
+      def validate_data(data)
+        # Use same validation as KMeans for consistency
+        DataValidator.validate_clustering(data, check_finite: false)
+      end
+    end
+  end
+end
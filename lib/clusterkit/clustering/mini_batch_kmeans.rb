@@ -0,0 +1,118 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # Mini-batch K-means for large or streaming datasets - matching KMeans API pattern
+    #
+    # Centroids are updated from small batches with per-centroid learning rates,
+    # so data can be fed incrementally with partial_fit instead of held in memory.
+    class MiniBatchKMeans
+      attr_reader :k, :batch_size, :max_iter, :tol, :centroids, :counts, :labels, :inertia,
+                  :n_iter, :n_relocations
+
+      # Initialize mini-batch K-means clusterer
+      # @param k [Integer] Number of clusters
+      # @param batch_size [Integer] Points sampled per update step in fit (default: 1024)
+      # @param max_iter [Integer] Maximum number of mini-batch steps in fit (default: 100)
+      # @param tol [Float] fit stops once a step moves the centroids by less than this
+      #   total squared shift, relative to the mean feature variance (default: 1e-4)
+      # @param random_seed [Integer] Random seed for reproducibility (optional)
+      def initialize(k:, batch_size: 1024, max_iter: 100, tol: 1e-4, random_seed: nil)
+        raise ArgumentError, "k must be positive" unless k > 0
+        raise ArgumentError, "batch_size must be positive" unless batch_size > 0
+        raise ArgumentError, "tol must be non-negative" unless tol.is_a?(Numeric) && tol >= 0
+        @k = k
+        @batch_size = batch_size
+        @max_iter = max_iter
+        @tol = tol
+        @random_seed = random_seed
+        @fitted = false
+      end
+
+      # Fit the model on a full dataset by sampling mini-batches
+      # @param data [Array] 2D array of data points
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        validate_data(data)
+
+        result = Clustering.minibatch_kmeans_rust(data, @k, @batch_size, @max_iter, @tol.to_f, @random_seed)
+        @labels = result["labels"]
+        @centroids = result["centroids"]
+        @counts = result["counts"]
+        @inertia = result["inertia"]
+        @n_iter = result["n_iter"]
+        @converged = result["converged"]
+        @n_relocations = result["n_relocations"]
+        @fitted = true
+
+        self
+      end
+
+      # Update the model with one batch of data
+      # The first batch initializes the centroids with k-means++ and must contain at least k points
+      # @param batch [Array] 2D array of data points
+      # @return [self] Returns self for method chaining
+      def partial_fit(batch)
+        validate_data(batch)
+
+        result = Clustering.minibatch_partial_fit_rust(batch, @centroids, @counts, @k, @random_seed)
+        @centroids = result["centroids"]
+        @counts = result["counts"]
+        # Labels, inertia and the run statistics describe a full fit; they are stale once streaming starts
+        @labels = nil
+        @inertia = nil
+        @n_iter = nil
+        @converged = nil
+        @n_relocations = nil
+        @fitted = true
+
+        self
+      end
+
+      # Predict cluster labels for new data
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels
+      def predict(data)
+        raise NotFittedError, "Model must be fitted before predict" unless fitted?
+        validate_data(data)
+
+        Clustering.kmeans_predict_rust(data, @centroids, "euclidean")
+      end
+
+      # Fit the model and return labels
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Check if fit stopped before reaching max_iter
+      # @return [Boolean] True if the centroids moved less than tol in the last step
+      def converged?
+        @converged == true
+      end
+
+      # Get cluster centers
+      # @return [Array] 2D array of cluster centers
+      def cluster_centers
+        @centroids
+      end
+
+      private
+
+      def validate_data(data)
+        # Use same validation as KMeans for consistency
+        DataValidator.validate_clustering(data, check_finite: false)
+      end
+    end
+  end
+end
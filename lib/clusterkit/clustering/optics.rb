@@ -0,0 +1,175 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # OPTICS clustering algorithm - matching KMeans API pattern
+    #
+    # Orders the points so that each dense region forms a contiguous run, recording for
+    # every point its reachability distance from the points before it. Plotting
+    # reachability[ordering] gives the reachability plot, where clusters show up as
+    # valleys at every density level at once:
+    # - ordering: point indices in visiting order
+    # - reachability: per point, Float::INFINITY for the first point of each region
+    # - core_distances: per point, distance to its min_samples-th neighbor (itself
+    #   included), Float::INFINITY beyond max_eps
+    # - predecessor: per point, the point it was reached from (-1 if none)
+    #
+    # Labels are extracted from the plot with cluster_method 'xi' (valleys bounded by
+    # steep slopes; cluster_hierarchy lists them as [start, end] positions in the
+    # ordering, nested clusters first) or 'dbscan' (a cut at eps, equivalent to DBSCAN
+    # with that radius). extract_dbscan cuts the fitted plot at any other eps.
+    class OPTICS
+      METRICS = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine', 'chebyshev', 'precomputed'].freeze
+      CLUSTER_METHODS = ['xi', 'dbscan'].freeze
+
+      attr_reader :min_samples, :max_eps, :metric, :cluster_method, :eps, :xi, :min_cluster_size,
+                  :predecessor_correction,
+                  :labels, :core_sample_indices, :ordering, :reachability, :core_distances,
+                  :predecessor, :cluster_hierarchy
+
+      # Initialize OPTICS clusterer (matches KMeans pattern)
+      # @param min_samples [Integer] Neighbors, the point included, that make a core point (default: 5)
+      # @param max_eps [Float] Largest neighborhood radius considered; smaller values speed up
+      #   fitting (default: Float::INFINITY)
+      # @param metric [String] Distance metric: 'euclidean' ('l2'), 'manhattan' ('l1'), 'cosine',
+      #   'chebyshev' or 'precomputed', in which case fit expects an n x n distance matrix
+      #   (default: 'euclidean')
+      # @param cluster_method [String] 'xi' or 'dbscan' (default: 'xi')
+      # @param eps [Float, nil] Cut for 'dbscan', at most max_eps; nil means max_eps (default: nil)
+      # @param xi [Float] Minimum relative steepness of a cluster boundary for 'xi', between 0 and 1
+      #   (default: 0.05)
+      # @param min_cluster_size [Integer, nil] Smallest 'xi' cluster; nil means min_samples (default: nil)
+      # @param predecessor_correction [Boolean] Trim 'xi' clusters whose last points were reached
+      #   from outside them (default: true)
+      def initialize(min_samples: 5, max_eps: Float::INFINITY, metric: 'euclidean', cluster_method: 'xi',
+                     eps: nil, xi: 0.05, min_cluster_size: nil, predecessor_correction: true)
+        # Symbols work as well as the documented Strings
+        metric = metric.to_s if metric.is_a?(Symbol)
+        cluster_method = cluster_method.to_s if cluster_method.is_a?(Symbol)
+        raise ArgumentError, "min_samples must be positive" unless min_samples > 0
+        raise ArgumentError, "max_eps must be positive" unless max_eps > 0
+        raise ArgumentError, "xi must be between 0 and 1" unless xi > 0 && xi < 1
+
+        unless min_cluster_size.nil? || min_cluster_size > 0
+          raise ArgumentError, "min_cluster_size must be positive or nil"
+        end
+
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+
+        unless CLUSTER_METHODS.include?(cluster_method)
+          raise ArgumentError, "cluster_method must be one of: #{CLUSTER_METHODS.join(', ')}"
+        end
+
+        if cluster_method == 'dbscan'
+          cut = eps || max_eps
+          raise ArgumentError, "cluster_method 'dbscan' needs a finite eps or max_eps" unless cut.to_f.finite?
+          raise ArgumentError, "eps must be positive" unless cut > 0
+          raise ArgumentError, "eps must not exceed max_eps" if cut > max_eps
+        end
+
+        @min_samples = min_samples
+        @max_eps = max_eps.to_f
+        @metric = metric
+        @cluster_method = cluster_method
+        @eps = eps&.to_f
+        @xi = xi.to_f
+        @min_cluster_size = min_cluster_size
+        @predecessor_correction = predecessor_correction ? true : false
+        @fitted = false
+      end
+
+      # Fit the OPTICS model (matches KMeans.fit)
+      # @param data [Array] 2D array of data points, or a distance matrix for 'precomputed'
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        DataValidator.validate_clustering(data, check_finite: false)
+
+        result = Clustering.optics_rust(
+          data, @min_samples, @max_eps, @metric, @cluster_method, @eps, @xi,
+          @min_cluster_size, @predecessor_correction
+        )
+
+        @labels = result["labels"]
+        @core_sample_indices = result["core_sample_indices"]
+        @ordering = result["ordering"]
+        @reachability = result["reachability"]
+        @core_distances = result["core_distances"]
+        @predecessor = result["predecessor"]
+        @cluster_hierarchy = result["cluster_hierarchy"]
+        @fitted = true
+
+        self
+      end
+
+      # Fit the model and return labels (matches KMeans.fit_predict)
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels (-1 for noise)
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Labels DBSCAN would give with radius eps, read off the fitted reachability plot
+      # without recomputing distances; the fitted labels are left unchanged
+      # @param eps [Float] Neighborhood radius, at most max_eps
+      # @return [Array] Cluster labels (-1 for noise)
+      def extract_dbscan(eps)
+        raise NotFittedError, "Model must be fitted before extract_dbscan" unless fitted?
+        raise ArgumentError, "eps must not exceed max_eps" if eps > @max_eps
+
+        Clustering.optics_extract_dbscan_rust(@reachability, @core_distances, @ordering, eps.to_f)
+      end
+
+      # Check if model has been fitted (matches KMeans.fitted?)
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Get number of clusters found
+      # @return [Integer] Number of clusters (excluding noise)
+      def n_clusters
+        return 0 unless fitted?
+        @labels.uniq.reject { |l| l == -1 }.length
+      end
+
+      # Get the number of noise points
+      # @return [Integer] Number of points labeled as noise
+      def n_noise_points
+        return 0 unless fitted?
+        @labels.count(-1)
+      end
+    end
+
+    # Module-level convenience methods (matching KMeans pattern)
+    class << self
+      # Perform OPTICS clustering
+      # @param data [Array] 2D array of data points
+      # @param min_samples [Integer] Neighbors that make a core point
+      # @param max_eps [Float] Largest neighborhood radius considered
+      # @param metric [String] Distance metric
+      # @param options [Hash] Further OPTICS options (cluster_method, eps, xi, min_cluster_size,
+      #   predecessor_correction)
+      # @return [Hash] Result hash with :labels, :core_sample_indices, :ordering, :reachability,
+      #   :core_distances, :predecessor, :cluster_hierarchy and :n_clusters
+      def optics(data, min_samples: 5, max_eps: Float::INFINITY, metric: 'euclidean', **options)
+        clusterer = OPTICS.new(min_samples: min_samples, max_eps: max_eps, metric: metric, **options)
+        clusterer.fit(data)
+        {
+          labels: clusterer.labels,
+          core_sample_indices: clusterer.core_sample_indices,
+          ordering: clusterer.ordering,
+          reachability: clusterer.reachability,
+          core_distances: clusterer.core_distances,
+          predecessor: clusterer.predecessor,
+          cluster_hierarchy: clusterer.cluster_hierarchy,
+          n_clusters: clusterer.n_clusters
+        }
+      end
+    end
+  end
+end
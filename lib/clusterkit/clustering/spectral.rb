@@ -0,0 +1,116 @@
+# frozen_string_literal: true
+
+require_relative '../data_validator'
+
+module ClusterKit
+  module Clustering
+    # Spectral clustering - matching KMeans API pattern
+    #
+    # Clusters the points by how they connect rather than by distance to a center, so it
+    # finds non-convex clusters such as rings and crescents that KMeans splits:
+    # - each point is joined to its n_neighbors nearest neighbors, found with an HNSW index,
+    #   so memory and time grow with n * n_neighbors rather than n²
+    # - the eigenvectors of the n_clusters smallest eigenvalues of the graph's normalized
+    #   Laplacian give every point a coordinate per cluster (the embedding)
+    # - k-means on the embedding, with each row scaled to unit length, gives the labels
+    #
+    # Eigenvalues close to 0 mean parts of the graph that barely connect; a jump after the
+    # first few suggests how many clusters the data has.
+    class SpectralClustering
+      METRICS = ['euclidean', 'l2', 'manhattan', 'l1', 'cosine', 'chebyshev', 'precomputed'].freeze
+
+      attr_reader :n_clusters, :n_neighbors, :metric, :n_init, :random_seed,
+                  :labels, :embedding, :eigenvalues, :inertia
+
+      # Initialize spectral clusterer (matches KMeans pattern)
+      # @param n_clusters [Integer] Number of clusters (default: 2)
+      # @param n_neighbors [Integer] Neighbors per point in the affinity graph; too few can split
+      #   a cluster into pieces that do not connect (default: 10)
+      # @param metric [String] Distance metric for the neighbor search: 'euclidean' ('l2'),
+      #   'manhattan' ('l1'), 'cosine', 'chebyshev' or 'precomputed', in which case fit expects
+      #   an n x n distance matrix (default: 'euclidean')
+      # @param n_init [Integer] Number of k-means initializations on the embedding (default: 10)
+      # @param random_seed [Integer, nil] Seed for a reproducible graph, eigenvectors and
+      #   k-means (default: nil)
+      def initialize(n_clusters: 2, n_neighbors: 10, metric: 'euclidean', n_init: 10, random_seed: nil)
+        # Symbols work as well as the documented Strings
+        metric = metric.to_s if metric.is_a?(Symbol)
+        raise ArgumentError, "n_clusters must be positive" unless n_clusters > 0
+        raise ArgumentError, "n_neighbors must be positive" unless n_neighbors > 0
+        raise ArgumentError, "n_init must be positive" unless n_init > 0
+
+        unless METRICS.include?(metric)
+          raise ArgumentError, "metric must be one of: #{METRICS.join(', ')}"
+        end
+
+        @n_clusters = n_clusters
+        @n_neighbors = n_neighbors
+        @metric = metric
+        @n_init = n_init
+        @random_seed = random_seed
+        @fitted = false
+      end
+
+      # Fit the spectral clustering model (matches KMeans.fit)
+      # @param data [Array] 2D array of data points, or a distance matrix for 'precomputed'
+      # @return [self] Returns self for method chaining
+      def fit(data)
+        DataValidator.validate_clustering(data, check_finite: false)
+
+        result = Clustering.spectral_rust(data, @n_clusters, @n_neighbors, @metric, @n_init, @random_seed)
+
+        @labels = result["labels"]
+        @embedding = result["embedding"]
+        @eigenvalues = result["eigenvalues"]
+        @converged = result["converged"]
+        @inertia = result["inertia"]
+        @fitted = true
+
+        if !@converged && ::ClusterKit.configuration.verbose
+          warn "SpectralClustering: eigenvectors did not converge; labels may be less reliable"
+        end
+
+        self
+      end
+
+      # Fit the model and return labels (matches KMeans.fit_predict)
+      # @param data [Array] 2D array of data points
+      # @return [Array] Cluster labels
+      def fit_predict(data)
+        fit(data)
+        @labels
+      end
+
+      # Check if model has been fitted (matches KMeans.fitted?)
+      # @return [Boolean] True if fitted
+      def fitted?
+        @fitted
+      end
+
+      # Whether the eigenvector computation converged
+      # @return [Boolean]
+      def converged?
+        @converged ? true : false
+      end
+    end
+
+    # Module-level convenience methods (matching KMeans pattern)
+    class << self
+      # Perform spectral clustering
+      # @param data [Array] 2D array of data points
+      # @param n_clusters [Integer] Number of clusters
+      # @param n_neighbors [Integer] Neighbors per point in the affinity graph
+      # @param options [Hash] Further options (metric, n_init, random_seed)
+      # @return [Hash] Result hash with :labels, :embedding and :eigenvalues
+      def spectral(data, n_clusters: 2, n_neighbors: 10, **options)
+        clusterer = SpectralClustering.new(n_clusters: n_clusters, n_neighbors: n_neighbors, **options)
+        clusterer.fit(data)
+        {
+          labels: clusterer.labels,
+          embedding: clusterer.embedding,
+          eigenvalues: clusterer.eigenvalues
+        }
+      end
+    end
+  end
+end
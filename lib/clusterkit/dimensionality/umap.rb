@@ -158,6 +158,9 @@ module ClusterKit
     private
 
     def handle_umap_error(error, data)
+      # Typed errors from the native extension already say what went wrong
+      raise error if error.is_a?(::ClusterKit::ConvergenceError) || error.is_a?(::ClusterKit::InvalidInputError)
+
       error_msg = error.message
       n_samples = data.size
 
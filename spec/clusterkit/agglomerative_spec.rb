@@ -0,0 +1,129 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::AgglomerativeClustering do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters
+    srand(7)
+    cluster1 = 20.times.map { [rand, rand] }
+    cluster2 = 20.times.map { [rand + 10, rand + 10] }
+    cluster3 = 20.times.map { [rand + 20, rand] }
+    cluster1 + cluster2 + cluster3
+  }
+
+  # Two pairs one unit apart and an outlier
+  let(:small_data) { [[0, 0], [0, 1], [5, 5], [5, 6], [20, 0]] }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new
+      expect(model.n_clusters).to eq(2)
+      expect(model.distance_threshold).to be_nil
+      expect(model.linkage).to eq('ward')
+      expect(model.metric).to eq('euclidean')
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(n_clusters: 0) }.to raise_error(ArgumentError, /n_clusters must be positive/)
+      expect { described_class.new(n_clusters: 2, distance_threshold: 1.0) }.to raise_error(ArgumentError, /not both/)
+      expect { described_class.new(distance_threshold: -1.0) }.to raise_error(ArgumentError, /non-negative/)
+      expect { described_class.new(linkage: 'centroid') }.to raise_error(ArgumentError, /linkage must be one of/)
+      expect { described_class.new(metric: 'hamming') }.to raise_error(ArgumentError, /metric must be one of/)
+      expect { described_class.new(metric: 'manhattan') }.to raise_error(ArgumentError, /ward linkage requires/)
+      expect { described_class.new(linkage: 'average', metric: 'manhattan') }.not_to raise_error
+    end
+  end
+
+  describe '#fit' do
+    %w[single complete average ward].each do |linkage|
+      it "recovers well-separated clusters with #{linkage} linkage" do
+        labels = described_class.new(n_clusters: 3, linkage: linkage).fit_predict(clustered_data)
+
+        expect(labels.uniq.sort).to eq([0, 1, 2])
+        expect(labels[0...20].uniq).to eq([0])
+        expect(labels[20...40].uniq).to eq([1])
+        expect(labels[40...60].uniq).to eq([2])
+      end
+    end
+
+    it 'returns a SciPy-style linkage matrix' do
+      model = described_class.new(linkage: 'average').fit(clustered_data)
+      n = clustered_data.size
+
+      expect(model.linkage_matrix.size).to eq(n - 1)
+      model.linkage_matrix.each_with_index do |(left, right, _distance, _size), row|
+        expect(left).to be < right
+        expect(right).to be < n + row
+      end
+      distances = model.linkage_matrix.map { |row| row[2] }
+      expect(distances).to eq(distances.sort)
+      expect(model.linkage_matrix.last[3]).to eq(n)
+    end
+
+    it 'computes merge distances for each linkage' do
+      expected = {
+        'single' => [1.0, 1.0, Math.sqrt(41), Math.sqrt(250)],
+        'complete' => [1.0, 1.0, Math.sqrt(61), Math.sqrt(401)],
+        'average' => [1.0, 1.0, (2 * Math.sqrt(50) + Math.sqrt(61) + Math.sqrt(41)) / 4],
+        'ward' => [1.0, 1.0, 10.0]
+      }
+      expected.each do |linkage, distances|
+        matrix = described_class.new(linkage: linkage).fit(small_data).linkage_matrix
+        distances.each_with_index do |distance, row|
+          expect(matrix[row][2]).to be_within(1e-9).of(distance)
+        end
+        expect(matrix[0][0..1]).to eq([0, 1])
+        expect(matrix[1][0..1]).to eq([2, 3])
+        expect(matrix[2][0..1]).to eq([5, 6])
+      end
+    end
+
+    it 'cuts the tree at a distance threshold' do
+      model = described_class.new(distance_threshold: 5.0, linkage: 'average').fit(small_data)
+      expect(model.labels).to eq([0, 0, 1, 1, 2])
+      expect(model.n_clusters_found).to eq(3)
+      expect(model.n_clusters).to be_nil
+
+      # Merges at exactly the threshold are undone
+      untouched = described_class.new(distance_threshold: 1.0, linkage: 'single').fit(small_data)
+      expect(untouched.labels).to eq([0, 1, 2, 3, 4])
+    end
+
+    it 'matches euclidean results on a precomputed distance matrix' do
+      distances = small_data.map do |a|
+        small_data.map { |b| Math.sqrt(a.zip(b).sum { |x, y| (x - y)**2 }) }
+      end
+      euclidean = described_class.new(linkage: 'complete').fit(small_data)
+      precomputed = described_class.new(linkage: 'complete', metric: 'precomputed').fit(distances)
+
+      expect(precomputed.labels).to eq(euclidean.labels)
+      precomputed.linkage_matrix.zip(euclidean.linkage_matrix).each do |a, b|
+        expect(a[0..1]).to eq(b[0..1])
+        expect(a[2]).to be_within(1e-9).of(b[2])
+      end
+    end
+
+    it 'raises when n_clusters exceeds the number of samples' do
+      expect {
+        described_class.new(n_clusters: 10).fit(small_data)
+      }.to raise_error(ArgumentError, /between 1 and the number of samples/)
+    end
+
+    it 'requires exactly one cut in the native function' do
+      expect {
+        ClusterKit::Clustering.agglomerative_rust(small_data, nil, nil, 'ward', 'euclidean')
+      }.to raise_error(ArgumentError, /Exactly one of n_clusters and distance_threshold/)
+    end
+  end
+
+  describe 'ClusterKit::Clustering.agglomerative' do
+    it 'returns labels and the linkage matrix' do
+      result = ClusterKit::Clustering.agglomerative(clustered_data, n_clusters: 3)
+      expect(result.keys).to contain_exactly(:labels, :n_clusters, :linkage_matrix)
+      expect(result[:n_clusters]).to eq(3)
+    end
+  end
+end
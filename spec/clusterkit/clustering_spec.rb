@@ -26,6 +26,13 @@ RSpec.describe ClusterKit::Clustering do
         expect(kmeans.k).to eq(5)
         expect(kmeans.max_iter).to eq(100)
       end
+
+      it 'accepts Strings for the Symbol options' do
+        kmeans = described_class.new(k: 3, algorithm: 'elkan', empty_cluster: 'keep', metric: 'cosine')
+        expect(kmeans.algorithm).to eq(:elkan)
+        expect(kmeans.empty_cluster).to eq(:keep)
+        expect(kmeans.metric).to eq(:cosine)
+      end
     end
     
     describe '#fit' do
@@ -50,6 +57,131 @@ RSpec.describe ClusterKit::Clustering do
         expect(kmeans.inertia).to be_a(Float)
         expect(kmeans.inertia).to be > 0
       end
+
+      it 'reports iterations and convergence' do
+        kmeans.fit(simple_data)
+        expect(kmeans.n_iter).to be_a(Integer)
+        expect(kmeans.n_iter).to be_between(1, kmeans.max_iter)
+        expect(kmeans).to be_converged
+      end
+
+      it 'reports non-convergence when max_iter is reached first' do
+        data = 200.times.map { [rand * 100, rand * 100] }
+        kmeans = described_class.new(k: 8, max_iter: 1, tol: 0.0, random_seed: 42).fit(data)
+        expect(kmeans.n_iter).to eq(1)
+        expect(kmeans).not_to be_converged
+      end
+
+      it 'stops earlier with a looser tolerance' do
+        data = 500.times.map { [rand * 100, rand * 100] }
+        strict = described_class.new(k: 8, tol: 0.0, random_seed: 42).fit(data)
+        loose = described_class.new(k: 8, tol: 1e-1, random_seed: 42).fit(data)
+        expect(loose.n_iter).to be <= strict.n_iter
+      end
+
+      it 'rejects a negative tolerance' do
+        expect { described_class.new(k: 3, tol: -1.0) }.to raise_error(ArgumentError, /tol/)
+      end
+
+      it 'gives identical results with the elkan algorithm' do
+        data = 1000.times.map { [rand * 100, rand * 100, rand * 100] }
+        lloyd = described_class.new(k: 10, random_seed: 42, algorithm: :lloyd).fit(data)
+        elkan = described_class.new(k: 10, random_seed: 42, algorithm: :elkan).fit(data)
+        expect(elkan.labels).to eq(lloyd.labels)
+        expect(elkan.centroids).to eq(lloyd.centroids)
+        expect(elkan.inertia).to eq(lloyd.inertia)
+        expect(elkan.n_iter).to eq(lloyd.n_iter)
+      end
+
+      it 'rejects an unknown algorithm' do
+        expect { described_class.new(k: 3, algorithm: :hamerly) }.to raise_error(ArgumentError, /algorithm/)
+      end
+
+      # With this seed one centroid loses all of its points after the first update
+      let(:emptying_data) { [[1.0, 8.0], [9.0, 6.0], [8.0, 6.0], [4.0, 2.0], [3.0, 7.0]] }
+
+      it 'relocates empty clusters so every cluster keeps a point' do
+        kmeans = described_class.new(k: 3, random_seed: 42).fit(emptying_data)
+        expect(kmeans.empty_cluster).to eq(:relocate)
+        expect(kmeans.n_relocations).to be > 0
+        expect(kmeans.labels.uniq.sort).to eq([0, 1, 2])
+      end
+
+      it 'leaves the cluster empty with the keep policy' do
+        kmeans = described_class.new(k: 3, random_seed: 42, empty_cluster: :keep).fit(emptying_data)
+        expect(kmeans.n_relocations).to eq(0)
+        expect(kmeans.labels.uniq.size).to be < 3
+      end
+
+      it 'never relocates with the keep policy' do
+        data = 300.times.map { [rand * 100, rand * 100] }
+        kmeans = described_class.new(k: 30, random_seed: 42, empty_cluster: :keep).fit(data)
+        expect(kmeans.n_relocations).to eq(0)
+      end
+
+      it 'rejects an unknown empty cluster policy' do
+        expect { described_class.new(k: 3, empty_cluster: :drop) }.to raise_error(ArgumentError, /empty_cluster/)
+      end
+
+      it 'matches an unweighted fit when all weights are one' do
+        data = 300.times.map { [rand * 100, rand * 100] }
+        plain = described_class.new(k: 4, random_seed: 42).fit(data)
+        weighted = described_class.new(k: 4, random_seed: 42).fit(data, sample_weight: Array.new(data.size, 1.0))
+        expect(weighted.labels).to eq(plain.labels)
+        expect(weighted.centroids).to eq(plain.centroids)
+        expect(weighted.inertia).to eq(plain.inertia)
+      end
+
+      it 'scales inertia with the weights' do
+        data = 300.times.map { [rand * 100, rand * 100] }
+        plain = described_class.new(k: 4, random_seed: 42).fit(data)
+        weighted = described_class.new(k: 4, random_seed: 42).fit(data, sample_weight: Array.new(data.size, 2.0))
+        expect(weighted.centroids).to eq(plain.centroids)
+        expect(weighted.inertia).to eq(plain.inertia * 2)
+      end
+
+      it 'pulls centroids towards heavier points' do
+        data = [[0.0], [1.0], [10.0], [11.0]]
+        kmeans = described_class.new(k: 2, random_seed: 42).fit(data, sample_weight: [1.0, 3.0, 1.0, 0.0])
+        expect(kmeans.centroids.map(&:first).sort).to eq([0.75, 10.0])
+      end
+
+      it 'clusters by direction with the cosine metric' do
+        # Two directions at very different magnitudes
+        data = 20.times.flat_map { |i| [[1.0 * (i + 1), 0.1], [0.1, 1.0 * (i + 1)]] }
+        kmeans = described_class.new(k: 2, random_seed: 42, metric: :cosine).fit(data)
+
+        x_labels = data.each_index.select(&:even?).map { |i| kmeans.labels[i] }.uniq
+        y_labels = data.each_index.select(&:odd?).map { |i| kmeans.labels[i] }.uniq
+        expect(x_labels.size).to eq(1)
+        expect(y_labels.size).to eq(1)
+        expect(x_labels).not_to eq(y_labels)
+
+        kmeans.centroids.each do |centroid|
+          expect(Math.sqrt(centroid.sum { |v| v * v })).to be_within(1e-10).of(1.0)
+        end
+        expect(kmeans.predict([[1000.0, 1.0]])).to eq(x_labels)
+      end
+
+      it 'uses median centroids with the manhattan metric' do
+        # Each group has an outlier that would pull a mean but not a median
+        data = [0.0, 1.0, 2.0, 3.0, 10.0, 100.0, 101.0, 102.0, 103.0, 110.0].map { |v| [v, v] }
+        kmeans = described_class.new(k: 2, random_seed: 42, metric: :manhattan).fit(data)
+        expect(kmeans.centroids.sort).to eq([[2.0, 2.0], [102.0, 102.0]])
+        expect(kmeans.predict([[3.0, 2.0]])).to eq([kmeans.labels[0]])
+      end
+
+      it 'rejects an unknown metric and elkan with manhattan' do
+        expect { described_class.new(k: 3, metric: :chebyshev) }.to raise_error(ArgumentError, /metric/)
+        expect { described_class.new(k: 3, metric: :manhattan, algorithm: :elkan) }.to raise_error(ArgumentError, /elkan/)
+      end
+
+      it 'validates sample weights' do
+        data = [[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]
+        expect { kmeans.fit(data, sample_weight: [1.0, 1.0]) }.to raise_error(ArgumentError, /sample_weight has 2 entries/)
+        expect { kmeans.fit(data, sample_weight: [1.0, -1.0, 1.0]) }.to raise_error(ArgumentError, /non-negative/)
+        expect { kmeans.fit(data, sample_weight: [0.0, 0.0, 0.0]) }.to raise_error(ArgumentError, /positive sum/)
+      end
       
       it 'raises error for empty data' do
         expect {
@@ -99,7 +231,7 @@ RSpec.describe ClusterKit::Clustering do
       it 'raises error if not fitted' do
         expect {
           kmeans.predict(new_data)
-        }.to raise_error(RuntimeError, /Model must be fitted before predict/)
+        }.to raise_error(ClusterKit::NotFittedError, /Model must be fitted before predict/)
       end
     end
     
@@ -167,6 +299,116 @@ RSpec.describe ClusterKit::Clustering do
         score = described_class.silhouette_score(data, labels)
         expect(score).to eq(0.0)
       end
+
+      it 'matches the hand-computed value' do
+        score = described_class.silhouette_score([[0.0], [1.0], [10.0], [11.0]], [0, 0, 1, 1])
+        expected = (2 * (9.5 / 10.5) + 2 * (8.5 / 9.5)) / 4
+        expect(score).to be_within(1e-12).of(expected)
+      end
+
+      it 'excludes noise points by default' do
+        points = [[0.0], [1.0], [10.0], [11.0], [5.0]]
+        labels = [0, 0, 1, 1, -1]
+        expect(described_class.silhouette_score(points, labels))
+          .to eq(described_class.silhouette_score(points.first(4), labels.first(4)))
+        expect(described_class.silhouette_score(points, labels, exclude_noise: false))
+          .not_to eq(described_class.silhouette_score(points, labels))
+      end
+
+      it 'approximates the exact score with a sample' do
+        points = 400.times.map { |i| [(i % 2) * 10.0 + rand, rand] }
+        labels = 400.times.map { |i| i % 2 }
+        exact = described_class.silhouette_score(points, labels)
+        sampled = described_class.silhouette_score(points, labels, sample_size: 100, random_seed: 42)
+        expect(sampled).to be_within(0.05).of(exact)
+        expect(described_class.silhouette_score(points, labels, sample_size: 100, random_seed: 42)).to eq(sampled)
+      end
+
+      it 'rejects labels that do not match the data' do
+        expect { described_class.silhouette_score([[0.0], [1.0]], [0]) }.to raise_error(ArgumentError, /labels/)
+      end
+    end
+
+    describe '.davies_bouldin_score' do
+      it 'matches the hand-computed value' do
+        score = described_class.davies_bouldin_score([[0.0], [1.0], [10.0], [11.0]], [0, 0, 1, 1])
+        expect(score).to be_within(1e-12).of(0.1)
+      end
+
+      it 'is lower for better separated clusters' do
+        close = described_class.davies_bouldin_score([[0.0], [1.0], [3.0], [4.0]], [0, 0, 1, 1])
+        far = described_class.davies_bouldin_score([[0.0], [1.0], [30.0], [31.0]], [0, 0, 1, 1])
+        expect(far).to be < close
+      end
+
+      it 'requires at least two clusters' do
+        expect { described_class.davies_bouldin_score(data, [0] * data.size) }.to raise_error(ArgumentError, /2 and n_samples - 1/)
+      end
+    end
+
+    describe '.calinski_harabasz_score' do
+      it 'matches the hand-computed value' do
+        score = described_class.calinski_harabasz_score([[0.0], [1.0], [10.0], [11.0]], [0, 0, 1, 1])
+        expect(score).to be_within(1e-9).of(200.0)
+      end
+
+      it 'excludes noise points by default' do
+        score = described_class.calinski_harabasz_score([[0.0], [1.0], [10.0], [11.0], [50.0]], [0, 0, 1, 1, -1])
+        expect(score).to be_within(1e-9).of(200.0)
+      end
+    end
+
+    describe 'comparison metrics' do
+      let(:labels_true) { [0, 0, 0, 1, 1, 1] }
+      let(:labels_pred) { [0, 0, 1, 1, 2, 2] }
+
+      it 'matches reference values' do
+        expect(described_class.adjusted_rand_score(labels_true, labels_pred)).to be_within(1e-9).of(0.242424242)
+        expect(described_class.normalized_mutual_info_score(labels_true, labels_pred)).to be_within(1e-6).of(0.515804)
+        expect(described_class.adjusted_mutual_info_score(labels_true, labels_pred)).to be_within(1e-6).of(0.298792)
+        expect(described_class.fowlkes_mallows_score(labels_true, labels_pred)).to be_within(1e-6).of(0.471405)
+
+        h, c, v = described_class.homogeneity_completeness_v_measure(labels_true, labels_pred)
+        expect(h).to be_within(1e-6).of(0.666667)
+        expect(c).to be_within(1e-6).of(0.420620)
+        expect(v).to be_within(1e-6).of(0.515804)
+        expect(described_class.v_measure_score(labels_true, labels_pred)).to eq(v)
+      end
+
+      it 'scores zero completeness as zero V-measure with beta 0' do
+        # A single class is trivially homogeneous but split across clusters
+        h, c, v = described_class.homogeneity_completeness_v_measure([0, 0, 0, 0], [0, 1, 0, 1], beta: 0.0)
+        expect(h).to eq(1.0)
+        expect(c).to eq(0.0)
+        expect(v).to eq(0.0)
+      end
+
+      it 'scores permuted labels as identical' do
+        expect(described_class.adjusted_rand_score([0, 0, 1, 1], [1, 1, 0, 0])).to eq(1.0)
+        expect(described_class.adjusted_mutual_info_score([0, 0, 1, 1], [1, 1, 0, 0])).to be_within(1e-9).of(1.0)
+        expect(described_class.fowlkes_mallows_score([0, 0, 1, 1], [1, 1, 0, 0])).to be_within(1e-9).of(1.0)
+      end
+
+      it 'handles noise according to the noise policy' do
+        truth = [0, 0, 1, 1, -1]
+        pred = [0, 0, 1, 1, 0]
+
+        expect(described_class.adjusted_rand_score(truth, pred)).to eq(1.0)
+        expect(described_class.adjusted_rand_score(truth, pred, noise: :cluster)).to be < 1.0
+        expect(described_class.adjusted_rand_score(truth, pred, noise: :singletons)).to be < 1.0
+      end
+
+      it 'builds a contingency matrix' do
+        result = described_class.contingency_matrix([0, 0, 1, -1], [1, 1, 1, 0], noise: :cluster)
+        expect(result['true_labels']).to eq([-1, 0, 1])
+        expect(result['pred_labels']).to eq([0, 1])
+        expect(result['matrix']).to eq([[1, 0], [0, 2], [0, 1]])
+      end
+
+      it 'rejects mismatched labelings and unknown noise policies' do
+        expect { described_class.adjusted_rand_score([0, 1], [0]) }.to raise_error(ArgumentError, /labels_pred/)
+        expect { described_class.adjusted_rand_score([0, 1], [0, 1], noise: :drop) }.to raise_error(ArgumentError, /noise/)
+      end
     end
   end
   
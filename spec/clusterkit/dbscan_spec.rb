@@ -0,0 +1,98 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::DBSCAN do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters plus far outliers
+    srand(11)
+    cluster1 = 20.times.map { [rand * 2, rand * 2] }
+    cluster2 = 20.times.map { [rand * 2 + 5, rand * 2 + 5] }
+    cluster3 = 20.times.map { [rand * 2 + 10, rand * 2] }
+    noise = [[20, 20], [-5, -5], [15, 15], [8, -3], [3, 10]]
+    cluster1 + cluster2 + cluster3 + noise
+  }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new
+      expect(model.eps).to eq(0.5)
+      expect(model.min_samples).to eq(5)
+      expect(model.metric).to eq('euclidean')
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(eps: 0) }.to raise_error(ArgumentError, /eps must be positive/)
+      expect { described_class.new(eps: Float::INFINITY) }.to raise_error(ArgumentError, /eps must be positive/)
+      expect { described_class.new(min_samples: 0) }.to raise_error(ArgumentError, /min_samples must be positive/)
+      expect { described_class.new(metric: 'hamming') }.to raise_error(ArgumentError, /metric must be one of/)
+    end
+
+    it 'accepts a Symbol metric' do
+      expect(described_class.new(metric: :cosine).metric).to eq('cosine')
+    end
+  end
+
+  describe '#fit' do
+    it 'finds the dense clusters and labels outliers as noise' do
+      model = described_class.new(eps: 1.0, min_samples: 4).fit(clustered_data)
+
+      expect(model).to be_fitted
+      expect(model.n_clusters).to eq(3)
+      expect(model.labels[60..64]).to all(eq(-1))
+      expect(model.n_noise_points).to eq(5)
+      [0...20, 20...40, 40...60].each do |range|
+        expect(model.labels[range].uniq.size).to eq(1)
+      end
+      expect(model.labels.values_at(0, 20, 40).uniq.size).to eq(3)
+    end
+
+    it 'numbers clusters in order of their first core point' do
+      labels = described_class.new(eps: 1.0, min_samples: 4).fit_predict(clustered_data)
+      expect(labels.values_at(0, 20, 40)).to eq([0, 1, 2])
+    end
+
+    it 'reports core samples and attaches border points to their cluster' do
+      data = [[0.0, 0.0], [0.5, 0.0], [1.0, 0.0], [5.0, 5.0]]
+      model = described_class.new(eps: 0.6, min_samples: 3).fit(data)
+
+      expect(model.core_sample_indices).to eq([1])
+      expect(model.labels).to eq([0, 0, 0, -1])
+    end
+
+    it 'matches euclidean results on a precomputed distance matrix' do
+      distances = clustered_data.map do |a|
+        clustered_data.map { |b| Math.sqrt(a.zip(b).sum { |x, y| (x - y)**2 }) }
+      end
+      euclidean = described_class.new(eps: 1.0, min_samples: 4).fit(clustered_data)
+      precomputed = described_class.new(eps: 1.0, min_samples: 4, metric: 'precomputed').fit(distances)
+
+      expect(precomputed.labels).to eq(euclidean.labels)
+      expect(precomputed.core_sample_indices).to eq(euclidean.core_sample_indices)
+    end
+
+    it 'supports the other metrics' do
+      %w[manhattan chebyshev].each do |metric|
+        model = described_class.new(eps: 1.0, min_samples: 4, metric: metric).fit(clustered_data)
+        expect(model.labels[60..64]).to all(eq(-1))
+      end
+    end
+
+    it 'raises for an unknown metric passed to the native function' do
+      expect {
+        ClusterKit::Clustering.dbscan_rust(clustered_data, 1.0, 4, 'hamming')
+      }.to raise_error(ArgumentError, /Unknown metric/)
+    end
+  end
+
+  describe 'ClusterKit::Clustering.dbscan' do
+    it 'returns labels and core samples' do
+      result = ClusterKit::Clustering.dbscan(clustered_data, eps: 1.0, min_samples: 4)
+      expect(result.keys).to contain_exactly(:labels, :core_sample_indices, :n_clusters, :n_noise_points)
+      expect(result[:n_clusters]).to eq(3)
+      expect(result[:core_sample_indices]).to all(be < 60)
+    end
+  end
+end
@@ -149,6 +149,58 @@ RSpec.describe ClusterKit::Clustering::KMeans do
     end
   end
 
+  describe '.evaluate_k' do
+    let(:data) do
+      # Three well separated blobs in 2D
+      rng = Random.new(7)
+      [[0.0, 0.0], [5.0, 5.0], [0.0, 6.0]].flat_map do |cx, cy|
+        Array.new(30) { [cx + rng.rand(-0.8..0.8), cy + rng.rand(-0.8..0.8)] }
+      end
+    end
+
+    it 'reports inertia, gap statistic and silhouette per k' do
+      result = described_class.evaluate_k(data, k_range: 1..6, random_seed: 42)
+
+      expect(result[:k]).to eq([1, 2, 3, 4, 5, 6])
+      [:inertia, :gap, :gap_sd, :silhouette].each do |key|
+        expect(result[key].size).to eq(6)
+      end
+      expect(result[:silhouette].first).to eq(0.0)
+      expect(result[:gap_k]).to eq(3)
+      expect(result[:silhouette_k]).to eq(3)
+    end
+
+    it 'matches the elbow method inertia' do
+      result = described_class.evaluate_k(data, k_range: 2..4, random_seed: 42)
+      elbow = described_class.elbow_method(data, k_range: 2..4, random_seed: 42)
+
+      expect(result[:inertia]).to eq(elbow.values)
+    end
+
+    it 'is reproducible with a random seed' do
+      first = described_class.evaluate_k(data, k_range: 2..4, random_seed: 42)
+      second = described_class.evaluate_k(data, k_range: 2..4, random_seed: 42)
+
+      expect(first).to eq(second)
+    end
+
+    it 'skips the gap statistic and silhouette on request' do
+      result = described_class.evaluate_k(data, k_range: 2..4, n_references: 0, silhouette: false)
+
+      expect(result[:gap]).to be_nil
+      expect(result[:gap_k]).to be_nil
+      expect(result[:silhouette]).to be_nil
+      expect(result[:silhouette_k]).to be_nil
+    end
+
+    it 'chooses k = 1 for structureless data' do
+      rng = Random.new(3)
+      uniform = Array.new(90) { [rng.rand, rng.rand] }
+
+      expect(described_class.optimal_k(uniform, k_range: 1..6, criterion: :gap, random_seed: 42)).to eq(1)
+    end
+  end
+
   describe '.detect_optimal_k' do
     it 'detects clear elbow at k=3' do
       results = {2 => 1000.0, 3 => 100.0, 4 => 90.0, 5 => 85.0}
@@ -222,6 +274,15 @@ RSpec.describe ClusterKit::Clustering::KMeans do
       expect(optimal).to be_between(2, 4)
     end
     
+    it 'chooses k by the gap statistic or silhouette' do
+      expect(described_class.optimal_k(data, k_range: 2..6, criterion: :gap, random_seed: 42)).to eq(3)
+      expect(described_class.optimal_k(data, k_range: 2..6, criterion: :silhouette, random_seed: 42)).to eq(3)
+    end
+
+    it 'rejects unknown criteria' do
+      expect { described_class.optimal_k(data, criterion: :aic) }.to raise_error(ArgumentError, /criterion/)
+    end
+
     it 'can be used to create an optimally-configured kmeans' do
       optimal = described_class.optimal_k(data, k_range: 2..5)
       kmeans = described_class.new(k: optimal)
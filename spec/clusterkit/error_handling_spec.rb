@@ -31,6 +31,98 @@ RSpec.describe 'ClusterKit error handling' do
     it 'defines InvalidParameterError as subclass of Error' do
       expect(ClusterKit::InvalidParameterError).to be < ClusterKit::Error
     end
+
+    it 'defines InvalidInputError as subclass of Error' do
+      expect(ClusterKit::InvalidInputError).to be < ClusterKit::Error
+    end
+
+    it 'defines DimensionError as subclass of InvalidInputError' do
+      expect(ClusterKit::DimensionError).to be < ClusterKit::InvalidInputError
+    end
+
+    it 'defines DimensionMismatchError as subclass of DimensionError' do
+      expect(ClusterKit::DimensionMismatchError).to be < ClusterKit::DimensionError
+    end
+
+    it 'defines NotFittedError as subclass of Error' do
+      expect(ClusterKit::NotFittedError).to be < ClusterKit::Error
+    end
+
+    it 'defines PersistenceError as subclass of Error' do
+      expect(ClusterKit::PersistenceError).to be < ClusterKit::Error
+    end
+  end
+
+  describe 'Native model errors' do
+    let(:rust_umap_class) { ::ClusterKit.const_get(:RustUMAP) }
+
+    it 'raises NotFittedError when transforming with an unfitted native model' do
+      rust_umap = rust_umap_class.new({ n_components: 2, n_neighbors: 5 })
+      expect { rust_umap.transform([[1.0, 2.0]]) }.to raise_error(ClusterKit::NotFittedError, /No model loaded/)
+    end
+
+    it 'raises NotFittedError when saving an unfitted native model' do
+      rust_umap = rust_umap_class.new({ n_components: 2, n_neighbors: 5 })
+      expect { rust_umap.save_model("/tmp/never_written.bin") }.to raise_error(ClusterKit::NotFittedError)
+    end
+
+    it 'raises PersistenceError when loading a missing native model' do
+      expect { rust_umap_class.load_model("/nonexistent/model.bin") }.to raise_error(ClusterKit::PersistenceError)
+    end
+
+    it 'raises PersistenceError when loading a missing HNSW index' do
+      expect { ClusterKit::HNSW.load("/nonexistent/index") }.to raise_error(ClusterKit::PersistenceError, /metadata/)
+    end
+
+    it 'raises DimensionMismatchError for queries of the wrong dimension' do
+      index = ClusterKit::HNSW.new(dim: 3)
+      index.add_item([1.0, 2.0, 3.0], label: 'a')
+      expect { index.search([1.0, 2.0], k: 1) }.to raise_error(ClusterKit::DimensionMismatchError)
+    end
+
+    it 'raises InvalidInputError for HNSW spaces that are not implemented' do
+      expect { ClusterKit::HNSW.new(dim: 3, space: :cosine) }
+        .to raise_error(ClusterKit::InvalidInputError, /Cosine distance is not yet implemented/)
+      expect { ClusterKit::HNSW.new(dim: 3, space: :inner_product) }
+        .to raise_error(ClusterKit::InvalidInputError, /Inner product distance is not yet implemented/)
+    end
+  end
+
+  describe 'Native matrix conversion errors' do
+    def native_kmeans(data, k)
+      ClusterKit::Clustering.kmeans_rust(data, k, 10, nil, 1, 1e-4, "lloyd", "relocate", nil, "euclidean")
+    end
+
+    it 'raises InvalidInputError for empty data' do
+      expect { native_kmeans([], 2) }
+        .to raise_error(ClusterKit::InvalidInputError, /Data cannot be empty/)
+    end
+
+    it 'raises InvalidInputError for empty rows' do
+      expect { native_kmeans([[], []], 1) }
+        .to raise_error(ClusterKit::InvalidInputError, /rows cannot be empty/)
+    end
+
+    it 'raises InvalidInputError for non-numeric values' do
+      expect { native_kmeans([[1.0, "a"], [2.0, 3.0]], 1) }
+        .to raise_error(ClusterKit::InvalidInputError, /position \[0, 1\] is not numeric/)
+    end
+
+    it 'raises DimensionError for ragged rows' do
+      expect { native_kmeans([[1.0, 2.0], [3.0]], 1) }
+        .to raise_error(ClusterKit::DimensionError, /Row 1 has 1 elements, expected 2/)
+    end
+
+    it 'raises the same classes for the f32 conversion path' do
+      index = ClusterKit::HNSW.new(dim: 2)
+      expect { index.add_item([1.0, "a"], {}) }.to raise_error(ClusterKit::InvalidInputError, /not numeric/)
+      expect { index.add_item([1.0], {}) }.to raise_error(ClusterKit::DimensionError)
+    end
+
+    it 'raises DimensionError when predicting with mismatched centroids' do
+      expect { ClusterKit::Clustering.kmeans_predict_rust([[1.0, 2.0]], [[1.0, 2.0, 3.0]], "euclidean") }
+        .to raise_error(ClusterKit::DimensionMismatchError, /features/)
+    end
   end
   
   describe 'UMAP error handling' do
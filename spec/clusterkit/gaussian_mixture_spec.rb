@@ -0,0 +1,147 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::GaussianMixture do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters
+    srand(7)
+    cluster1 = 30.times.map { [rand, rand] }
+    cluster2 = 30.times.map { [rand + 10, rand + 10] }
+    cluster3 = 30.times.map { [rand + 20, rand] }
+    cluster1 + cluster2 + cluster3
+  }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new(n_components: 3)
+      expect(model.covariance_type).to eq(:full)
+      expect(model.tol).to eq(1e-3)
+      expect(model.reg_covar).to eq(1e-6)
+      expect(model.max_iter).to eq(100)
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(n_components: 0) }.to raise_error(ArgumentError, /n_components must be positive/)
+      expect { described_class.new(covariance_type: :tied) }.to raise_error(ArgumentError, /covariance_type/)
+      expect { described_class.new(reg_covar: -1.0) }.to raise_error(ArgumentError, /reg_covar/)
+      expect { described_class.new(n_init: 0) }.to raise_error(ArgumentError, /n_init/)
+    end
+
+    it 'accepts a String covariance_type' do
+      expect(described_class.new(covariance_type: 'diag').covariance_type).to eq(:diag)
+    end
+  end
+
+  describe '#fit' do
+    [:full, :diag, :spherical].each do |covariance_type|
+      it "separates well-separated clusters with #{covariance_type} covariances" do
+        model = described_class.new(n_components: 3, covariance_type: covariance_type, random_seed: 42)
+        labels = model.fit_predict(clustered_data)
+
+        expect(model).to be_converged
+        expect(labels.uniq.size).to eq(3)
+        [0...30, 30...60, 60...90].each do |range|
+          expect(labels[range].uniq.size).to eq(1)
+        end
+        expect(model.weights.sum).to be_within(1e-9).of(1.0)
+        model.weights.each { |weight| expect(weight).to be_within(1e-6).of(1.0 / 3) }
+        model.means.sort_by(&:first).zip([[0.5, 0.5], [10.5, 10.5], [20.5, 0.5]]).each do |mean, center|
+          mean.zip(center).each { |value, expected| expect(value).to be_within(0.3).of(expected) }
+        end
+      end
+    end
+
+    it 'returns covariances shaped by covariance type' do
+      full = described_class.new(n_components: 3, random_seed: 42).fit(clustered_data).covariances
+      expect(full.size).to eq(3)
+      full.each do |matrix|
+        expect(matrix.map(&:size)).to eq([2, 2])
+        expect(matrix[0][1]).to be_within(1e-12).of(matrix[1][0])
+      end
+
+      diag = described_class.new(n_components: 3, covariance_type: :diag, random_seed: 42).fit(clustered_data).covariances
+      expect(diag.map(&:size)).to eq([2, 2, 2])
+
+      spherical = described_class.new(n_components: 3, covariance_type: :spherical, random_seed: 42).fit(clustered_data).covariances
+      expect(spherical.size).to eq(3)
+      expect(spherical).to all(be > 0)
+    end
+
+    it 'captures correlated features with full covariances' do
+      srand(3)
+      line = 50.times.map { |i| [i * 0.1, i * 0.1 + rand * 0.1] }
+      covariance = described_class.new(n_components: 1).fit(line).covariances.first
+      correlation = covariance[0][1] / Math.sqrt(covariance[0][0] * covariance[1][1])
+      expect(correlation).to be > 0.9
+    end
+
+    it 'is reproducible with a random seed' do
+      a = described_class.new(n_components: 3, n_init: 3, random_seed: 5).fit(clustered_data)
+      b = described_class.new(n_components: 3, n_init: 3, random_seed: 5).fit(clustered_data)
+      expect(a.means).to eq(b.means)
+      expect(a.lower_bound).to eq(b.lower_bound)
+    end
+
+    it 'raises when n_components exceeds the number of samples' do
+      expect {
+        described_class.new(n_components: 5).fit([[0.0, 0.0], [1.0, 1.0]])
+      }.to raise_error(ArgumentError, /between 1 and the number of samples/)
+    end
+  end
+
+  describe 'scoring' do
+    let(:model) { described_class.new(n_components: 3, random_seed: 42).fit(clustered_data) }
+
+    it 'predicts the fitted labels for the training data' do
+      expect(model.predict(clustered_data)).to eq(model.labels)
+      expect(model.predict([[0.5, 0.5], [20.5, 0.5]])).to eq(model.labels.values_at(0, 60))
+    end
+
+    it 'returns membership probabilities that sum to one' do
+      probabilities = model.predict_proba(clustered_data)
+      expect(probabilities.size).to eq(clustered_data.size)
+      probabilities.each do |row|
+        expect(row.size).to eq(3)
+        expect(row.sum).to be_within(1e-9).of(1.0)
+      end
+      expect(probabilities[0][model.labels[0]]).to be > 0.99
+    end
+
+    it 'scores points by log-likelihood' do
+      scores = model.score_samples([[0.5, 0.5], [50.0, 50.0]])
+      expect(scores[0]).to be > scores[1]
+      expect(model.score(clustered_data)).to be_within(1e-9).of(model.score_samples(clustered_data).sum / 90)
+    end
+
+    it 'prefers the right number of components by BIC and AIC' do
+      one = described_class.new(n_components: 1).fit(clustered_data)
+      expect(model.bic(clustered_data)).to be < one.bic(clustered_data)
+      expect(model.aic(clustered_data)).to be < one.aic(clustered_data)
+      # 3 full 2D components: 3 * 3 covariance + 3 * 2 mean + 2 weight parameters
+      expect(model.bic(clustered_data) - model.aic(clustered_data)).to be_within(1e-9).of(17 * (Math.log(90) - 2))
+    end
+
+    it 'raises if not fitted' do
+      expect {
+        described_class.new(n_components: 2).predict(clustered_data)
+      }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
+    end
+
+    it 'raises for data with a different number of features' do
+      expect {
+        model.predict([[1.0, 2.0, 3.0]])
+      }.to raise_error(ClusterKit::DimensionMismatchError)
+    end
+  end
+
+  describe 'ClusterKit::Clustering.gmm' do
+    it 'returns labels, probabilities and information criteria' do
+      result = ClusterKit::Clustering.gmm(clustered_data, n_components: 3, random_seed: 42)
+      expect(result.keys).to contain_exactly(:labels, :probabilities, :weights, :means, :covariances, :bic, :aic)
+      expect(result[:labels].uniq.size).to eq(3)
+    end
+  end
+end
@@ -74,6 +74,211 @@ RSpec.describe ClusterKit::Clustering::HDBSCAN do
       expect { described_class.new(metric: 'manhattan') }.not_to raise_error
       expect { described_class.new(metric: 'l1') }.not_to raise_error
       expect { described_class.new(metric: 'cosine') }.not_to raise_error
+      expect { described_class.new(metric: 'chebyshev') }.not_to raise_error
+      expect { described_class.new(metric: 'precomputed') }.not_to raise_error
+    end
+  end
+
+  describe 'metrics' do
+    ['manhattan', 'chebyshev'].each do |metric|
+      it "finds the three clusters with #{metric} distance" do
+        hdbscan = described_class.new(min_samples: 3, min_cluster_size: 5, metric: metric)
+        hdbscan.fit(clustered_data)
+        expect(hdbscan.n_clusters).to eq(3)
+      end
+    end
+
+    it 'clusters by direction with cosine distance' do
+      # Two fans of points at evenly spaced angles near each axis, at growing radii
+      rays = 20.times.flat_map do |i|
+        angle = 0.01 * i
+        radius = 1.0 + i
+        [[radius * Math.cos(angle), radius * Math.sin(angle)], [radius * Math.sin(angle), radius * Math.cos(angle)]]
+      end
+      hdbscan = described_class.new(min_samples: 3, min_cluster_size: 5, metric: 'cosine')
+      labels = hdbscan.fit_predict(rays)
+
+      expect(hdbscan.n_clusters).to eq(2)
+      expect(labels.each_slice(2).map(&:first).uniq.size).to eq(1)
+    end
+
+    it 'matches euclidean results on a precomputed distance matrix' do
+      distances = clustered_data.map do |a|
+        clustered_data.map { |b| Math.sqrt(a.zip(b).map { |x, y| (x - y) * (x - y) }.inject(:+)) }
+      end
+
+      euclidean = described_class.new(min_samples: 3, min_cluster_size: 5).fit(clustered_data)
+      precomputed = described_class.new(min_samples: 3, min_cluster_size: 5, metric: 'precomputed').fit(distances)
+
+      expect(precomputed.labels).to eq(euclidean.labels)
+    end
+
+    it 'rejects a non-square precomputed matrix' do
+      hdbscan = described_class.new(metric: 'precomputed')
+      expect { hdbscan.fit([[0.0, 1.0, 2.0], [1.0, 0.0, 3.0]]) }.to raise_error(ClusterKit::DimensionError)
+    end
+
+    it 'raises ArgumentError for unknown metrics in the native call' do
+      expect {
+        ClusterKit::Clustering.hdbscan_rust(clustered_data, 3, 5, 'hamming', 0.0, 'eom', false, nil, 1.0, 'exact', 15, nil)
+      }.to raise_error(ArgumentError, /Unknown metric/)
+    end
+  end
+  
+  describe 'cluster selection options' do
+    def fit_with(**options)
+      described_class.new(min_samples: 3, min_cluster_size: 5, **options).fit(clustered_data)
+    end
+    
+    it 'finds at least as many clusters with leaf selection' do
+      expect(fit_with(cluster_selection_method: 'leaf').n_clusters).to be >= fit_with.n_clusters
+    end
+    
+    it 'merges clusters that split off below cluster_selection_epsilon' do
+      expect(fit_with(cluster_selection_epsilon: 100.0).n_clusters).to eq(2)
+    end
+    
+    it 'returns a single cluster only when allowed' do
+      expect(fit_with(cluster_selection_epsilon: 100.0, allow_single_cluster: true).n_clusters).to eq(1)
+    end
+    
+    it 'keeps clusters within max_cluster_size' do
+      hdbscan = fit_with(max_cluster_size: 10)
+      expect(hdbscan.cluster_indices.values.map(&:size)).to all(be <= 10)
+    end
+    
+    it 'accepts alpha' do
+      expect(fit_with(alpha: 1.5).n_clusters).to be >= 1
+    end
+    
+    it 'validates the options' do
+      expect { described_class.new(cluster_selection_method: 'best') }.to raise_error(ArgumentError, /cluster_selection_method/)
+      expect { described_class.new(cluster_selection_epsilon: -1.0) }.to raise_error(ArgumentError, /cluster_selection_epsilon/)
+      expect { described_class.new(max_cluster_size: 0) }.to raise_error(ArgumentError, /max_cluster_size/)
+      expect { described_class.new(alpha: 0) }.to raise_error(ArgumentError, /alpha/)
+    end
+  end
+  
+  describe 'hnsw algorithm' do
+    let(:exact) { described_class.new(min_samples: 3, min_cluster_size: 5).fit(clustered_data) }
+    
+    it 'matches the exact clustering on well separated data' do
+      hnsw = described_class.new(min_samples: 3, min_cluster_size: 5, algorithm: 'hnsw', random_seed: 42)
+      hnsw.fit(clustered_data)
+      
+      expect(hnsw.n_clusters).to eq(3)
+      expect(ClusterKit::Clustering.adjusted_rand_score(exact.labels, hnsw.labels, noise: :cluster)).to be > 0.9
+    end
+    
+    it 'joins the pieces of a sparse neighbor graph' do
+      hnsw = described_class.new(min_samples: 3, min_cluster_size: 5, algorithm: 'hnsw', n_neighbors: 3, random_seed: 42)
+      hnsw.fit(clustered_data)
+      
+      expect(hnsw.single_linkage_tree.size).to eq(clustered_data.size - 1)
+      expect(hnsw.single_linkage_tree.last[3]).to eq(clustered_data.size)
+      expect(hnsw.n_clusters).to eq(3)
+    end
+
+    it 'joins many well separated blobs into a single spanning tree' do
+      srand(46)
+      blobs = 8.times.flat_map do |row|
+        8.times.flat_map do |col|
+          10.times.map { [col * 100 + rand, row * 100 + rand] }
+        end
+      end
+      hnsw = described_class.new(min_samples: 3, min_cluster_size: 5, algorithm: 'hnsw', n_neighbors: 3, random_seed: 42)
+      hnsw.fit(blobs)
+
+      tree = hnsw.single_linkage_tree
+      expect(tree.size).to eq(blobs.size - 1)
+      expect(tree.last[3]).to eq(blobs.size)
+    end
+
+    it 'is reproducible with a random seed' do
+      first = described_class.new(algorithm: 'hnsw', random_seed: 7).fit(clustered_data)
+      second = described_class.new(algorithm: 'hnsw', random_seed: 7).fit(clustered_data)
+      
+      expect(first.labels).to eq(second.labels)
+    end
+
+    it 'accepts a negative random seed like the other estimators' do
+      hnsw = described_class.new(min_samples: 3, min_cluster_size: 5, algorithm: 'hnsw', random_seed: -1)
+      expect { hnsw.fit(clustered_data) }.not_to raise_error
+      expect(hnsw.n_clusters).to eq(3)
+    end
+    
+    it 'supports approximate prediction' do
+      hnsw = described_class.new(min_samples: 3, min_cluster_size: 5, algorithm: 'hnsw', random_seed: 42).fit(clustered_data)
+      expect(hnsw.predict([[1, 1], [100, 100]])).to eq([hnsw.labels[0], -1])
+    end
+    
+    it 'validates the options' do
+      expect { described_class.new(algorithm: 'kdtree') }.to raise_error(ArgumentError, /algorithm/)
+      expect { described_class.new(n_neighbors: 0) }.to raise_error(ArgumentError, /n_neighbors/)
+      expect { described_class.new(algorithm: 'hnsw', metric: 'precomputed') }.to raise_error(ArgumentError, /precomputed/)
+    end
+  end
+  
+  describe 'cluster representatives' do
+    let(:hdbscan) { described_class.new(min_samples: 3, min_cluster_size: 5).fit(clustered_data) }
+    
+    it 'returns a probability-weighted centroid per cluster' do
+      expect(hdbscan.centroids.size).to eq(hdbscan.n_clusters)
+      { 0 => [1, 1], 20 => [6, 6], 40 => [11, 1] }.each do |point, center|
+        centroid = hdbscan.centroids[hdbscan.labels[point]]
+        expect(centroid[0]).to be_within(0.5).of(center[0])
+        expect(centroid[1]).to be_within(0.5).of(center[1])
+      end
+    end
+    
+    it 'returns a medoid from each cluster' do
+      hdbscan.medoid_indices.each_with_index do |index, label|
+        expect(hdbscan.labels[index]).to eq(label)
+        expect(hdbscan.medoids[label]).to eq(clustered_data[index])
+      end
+    end
+    
+    it 'returns exemplar points from each cluster' do
+      expect(hdbscan.exemplar_indices.size).to eq(hdbscan.n_clusters)
+      hdbscan.exemplar_indices.each_with_index do |indices, label|
+        expect(indices).not_to be_empty
+        expect(indices.map { |index| hdbscan.labels[index] }.uniq).to eq([label])
+        expect(hdbscan.exemplars[label]).to eq(indices.map { |index| clustered_data[index] })
+      end
+    end
+    
+    it 'only returns indices for precomputed distances' do
+      matrix = clustered_data.map do |a|
+        clustered_data.map { |b| Math.sqrt(a.zip(b).map { |x, y| (x - y) * (x - y) }.inject(:+)) }
+      end
+      precomputed = described_class.new(min_samples: 3, min_cluster_size: 5, metric: 'precomputed').fit(matrix)
+      
+      expect(precomputed.centroids).to be_nil
+      expect(precomputed.medoids).to be_nil
+      expect(precomputed.medoid_indices).to eq(hdbscan.medoid_indices)
+      expect(precomputed.exemplar_indices).to eq(hdbscan.exemplar_indices)
+    end
+  end
+  
+  describe '#adjusted_parameters' do
+    it 'reports parameters the data could not support' do
+      hdbscan = described_class.new(min_samples: 10, min_cluster_size: 8).fit(small_data)
+      expect(hdbscan.adjusted_parameters).to eq(min_samples: [10, 4], min_cluster_size: [8, 5])
+    end
+    
+    it 'is empty when every parameter was used as given' do
+      hdbscan = described_class.new(min_samples: 3, min_cluster_size: 5).fit(clustered_data)
+      expect(hdbscan.adjusted_parameters).to eq({})
+    end
+    
+    it 'warns about adjustments in verbose mode' do
+      verbose = ClusterKit.configuration.verbose
+      ClusterKit.configuration.verbose = true
+      expect {
+        described_class.new(min_samples: 10).fit(small_data)
+      }.to output(/HDBSCAN: Adjusted min_samples from 10 to 4/).to_stderr
+    ensure
+      ClusterKit.configuration.verbose = verbose
     end
   end
   
@@ -107,11 +312,65 @@ RSpec.describe ClusterKit::Clustering::HDBSCAN do
       expect(hdbscan.outlier_scores).to all(be_a(Numeric))
     end
     
+    it 'computes membership probabilities from the condensed tree' do
+      hdbscan.fit(clustered_data)
+      hdbscan.labels.zip(hdbscan.probabilities).each do |label, probability|
+        expect(probability).to be_between(0.0, 1.0)
+        expect(probability).to eq(0.0) if label == -1
+      end
+      # Points deep inside a cluster have full membership
+      expect(hdbscan.probabilities).to include(1.0)
+    end
+
+    it 'scores isolated points as the strongest outliers' do
+      hdbscan.fit(clustered_data)
+      expect(hdbscan.outlier_scores).to all(be_between(0.0, 1.0))
+
+      # [20, 20], [-5, -5] and [15, 15] are more anomalous than any cluster member
+      far_outlier_scores = hdbscan.outlier_scores[60..62]
+      cluster_scores = hdbscan.outlier_scores[0...60]
+      expect(far_outlier_scores.min).to be > cluster_scores.max
+    end
+
     it 'sets cluster_persistence hash' do
       hdbscan.fit(clustered_data)
       expect(hdbscan.cluster_persistence).to be_a(Hash)
     end
     
+    it 'reports persistence and stability for every cluster' do
+      hdbscan.fit(clustered_data)
+      labels = hdbscan.labels.uniq.reject { |l| l == -1 }.sort
+
+      expect(hdbscan.cluster_persistence.keys.sort).to eq(labels)
+      expect(hdbscan.cluster_persistence.values).to all(be_between(0.0, 1.0))
+      expect(hdbscan.cluster_stability.keys.sort).to eq(labels)
+      expect(hdbscan.cluster_stability.values).to all(be > 0)
+    end
+
+    it 'exports the condensed tree' do
+      hdbscan.fit(clustered_data)
+      n = clustered_data.size
+      tree = hdbscan.condensed_tree
+
+      expect(tree).to all(have_attributes(size: 4))
+      # Every point leaves the tree exactly once
+      points = tree.map { |_, child, _, _| child }.select { |child| child < n }
+      expect(points.sort).to eq((0...n).to_a)
+      # Clusters hang below the root, numbered from n_samples
+      expect(tree.map(&:first).min).to eq(n)
+    end
+
+    it 'exports the single-linkage tree in SciPy format' do
+      hdbscan.fit(clustered_data)
+      n = clustered_data.size
+      linkage = hdbscan.single_linkage_tree
+
+      expect(linkage.size).to eq(n - 1)
+      expect(linkage.last[3]).to eq(n)
+      distances = linkage.map { |row| row[2] }
+      expect(distances).to eq(distances.sort)
+    end
+
     it 'identifies noise points with -1 label' do
       hdbscan.fit(clustered_data)
       noise_labels = hdbscan.labels.select { |l| l == -1 }
@@ -152,13 +411,69 @@ RSpec.describe ClusterKit::Clustering::HDBSCAN do
   end
   
   describe '#predict' do
-    let(:hdbscan) { described_class.new }
+    let(:hdbscan) { described_class.new(min_samples: 3, min_cluster_size: 5) }
+    
+    it 'assigns new points to the cluster they fall in' do
+      hdbscan.fit(clustered_data)
+      labels = hdbscan.predict([[1, 1], [6, 6], [11, 1]])
+      
+      expect(labels).to eq([hdbscan.labels[0], hdbscan.labels[20], hdbscan.labels[40]])
+      expect(labels.uniq.size).to eq(3)
+    end
     
-    it 'raises NotImplementedError' do
+    it 'labels distant points as noise' do
       hdbscan.fit(clustered_data)
+      expect(hdbscan.predict([[100, 100]])).to eq([-1])
+    end
+    
+    it 'reproduces the labels of the training points' do
+      hdbscan.fit(clustered_data)
+      expect(hdbscan.predict(clustered_data)).to eq(hdbscan.labels)
+    end
+    
+    it 'raises error if not fitted' do
       expect {
         hdbscan.predict([[0, 0]])
-      }.to raise_error(NotImplementedError, /HDBSCAN does not support prediction/)
+      }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
+    end
+    
+    it 'raises error on a feature count mismatch' do
+      hdbscan.fit(clustered_data)
+      expect {
+        hdbscan.predict([[0, 0, 0]])
+      }.to raise_error(ClusterKit::DimensionMismatchError)
+    end
+  end
+  
+  describe '#approximate_predict' do
+    let(:hdbscan) { described_class.new(min_samples: 3, min_cluster_size: 5) }
+    
+    it 'returns labels and membership probabilities' do
+      hdbscan.fit(clustered_data)
+      labels, probabilities = hdbscan.approximate_predict([[1, 1], [100, 100]])
+      
+      expect(labels.first).to eq(hdbscan.labels[0])
+      expect(labels.last).to eq(-1)
+      expect(probabilities.first).to be_between(0.0, 1.0)
+      expect(probabilities.last).to eq(0.0)
+    end
+    
+    it 'accepts distances to the training points for precomputed metrics' do
+      distance = ->(a, b) { Math.sqrt(a.zip(b).map { |x, y| (x - y) * (x - y) }.inject(:+)) }
+      matrix = clustered_data.map { |a| clustered_data.map { |b| distance.(a, b) } }
+      euclidean = described_class.new(min_samples: 3, min_cluster_size: 5).fit(clustered_data)
+      precomputed = described_class.new(min_samples: 3, min_cluster_size: 5, metric: 'precomputed').fit(matrix)
+      
+      queries = [[1, 1], [6, 6], [100, 100]]
+      rows = queries.map { |q| clustered_data.map { |b| distance.(q, b) } }
+      
+      expect(precomputed.approximate_predict(rows)).to eq(euclidean.approximate_predict(queries))
+    end
+    
+    it 'raises NotFittedError if not fitted' do
+      expect {
+        hdbscan.approximate_predict([[0, 0]])
+      }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
     end
   end
   
@@ -319,6 +634,11 @@ RSpec.describe ClusterKit::Clustering::HDBSCAN do
         expect(result).to have_key(:n_clusters)
         expect(result).to have_key(:noise_ratio)
         expect(result).to have_key(:cluster_persistence)
+        expect(result).to have_key(:condensed_tree)
+        expect(result).to have_key(:single_linkage_tree)
+        expect(result).to have_key(:centroids)
+        expect(result).to have_key(:medoids)
+        expect(result).to have_key(:exemplars)
         
         expect(result[:labels]).to be_a(Array)
         expect(result[:labels].size).to eq(clustered_data.size)
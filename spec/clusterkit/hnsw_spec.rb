@@ -56,8 +56,8 @@ RSpec.describe ClusterKit::HNSW do
     end
 
     it 'raises error for wrong dimension' do
-      expect { index.add_item([1.0, 2.0], {}) }.to raise_error(ArgumentError, /dimension mismatch/)
-      expect { index.add_item([1.0, 2.0, 3.0, 4.0], {}) }.to raise_error(ArgumentError, /dimension mismatch/)
+      expect { index.add_item([1.0, 2.0], {}) }.to raise_error(ClusterKit::DimensionError, /dimension mismatch/)
+      expect { index.add_item([1.0, 2.0, 3.0, 4.0], {}) }.to raise_error(ClusterKit::DimensionError, /dimension mismatch/)
     end
 
     it 'raises error for duplicate labels' do
@@ -37,6 +37,39 @@ RSpec.describe "KMeans Random Seeding" do
       # Should be identical with working seeding
       expect(labels1).to eq(labels2)
     end
+
+    it "keeps the lowest-inertia run when n_init is greater than 1" do
+      single = ClusterKit::Clustering::KMeans.new(k: 3, random_seed: 42, n_init: 1).fit(test_data)
+      multi = ClusterKit::Clustering::KMeans.new(k: 3, random_seed: 42, n_init: 8).fit(test_data)
+
+      expect(multi.inertia).to be <= single.inertia
+    end
+
+    it "is reproducible with n_init and a seed" do
+      kmeans1 = ClusterKit::Clustering::KMeans.new(k: 3, random_seed: 42, n_init: 5).fit(test_data)
+      kmeans2 = ClusterKit::Clustering::KMeans.new(k: 3, random_seed: 42, n_init: 5).fit(test_data)
+
+      expect(kmeans1.centroids).to eq(kmeans2.centroids)
+      expect(kmeans1.labels).to eq(kmeans2.labels)
+      expect(kmeans1.inertia).to eq(kmeans2.inertia)
+    end
+
+    it "rejects a non-positive n_init" do
+      expect { ClusterKit::Clustering::KMeans.new(k: 3, n_init: 0) }.to raise_error(ArgumentError, /n_init/)
+    end
+
+    it "stays bit-for-bit reproducible when work is split across threads" do
+      # Large enough that centroid sums are accumulated in several parallel chunks
+      srand(54321)
+      large_data = 10_000.times.map { |i| 8.times.map { (i % 4) * 5.0 + rand } }
+
+      kmeans1 = ClusterKit::Clustering::KMeans.new(k: 4, random_seed: 7).fit(large_data)
+      kmeans2 = ClusterKit::Clustering::KMeans.new(k: 4, random_seed: 7).fit(large_data)
+
+      expect(kmeans1.centroids).to eq(kmeans2.centroids)
+      expect(kmeans1.inertia).to eq(kmeans2.inertia)
+      expect(kmeans1.labels).to eq(kmeans2.labels)
+    end
     
     it "demonstrates deterministic behavior across multiple runs" do
       # Multiple runs with same seed should be identical
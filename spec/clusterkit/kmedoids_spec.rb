@@ -0,0 +1,115 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::KMedoids do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters
+    srand(7)
+    cluster1 = 30.times.map { [rand, rand] }
+    cluster2 = 30.times.map { [rand + 10, rand + 10] }
+    cluster3 = 30.times.map { [rand + 20, rand] }
+    cluster1 + cluster2 + cluster3
+  }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new(k: 3)
+      expect(model.metric).to eq(:euclidean)
+      expect(model.algorithm).to eq(:pam)
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(k: 0) }.to raise_error(ArgumentError, /k must be positive/)
+      expect { described_class.new(k: 3, metric: :hamming) }.to raise_error(ArgumentError, /metric/)
+      expect { described_class.new(k: 3, algorithm: :alternate) }.to raise_error(ArgumentError, /algorithm/)
+    end
+
+    it 'accepts Strings for the Symbol options' do
+      model = described_class.new(k: 3, metric: 'manhattan', algorithm: 'clara')
+      expect(model.metric).to eq(:manhattan)
+      expect(model.algorithm).to eq(:clara)
+    end
+  end
+
+  describe '#fit' do
+    it 'picks actual data points as medoids' do
+      model = described_class.new(k: 3).fit(clustered_data)
+
+      expect(model.medoid_indices.size).to eq(3)
+      expect(model.medoids).to eq(model.medoid_indices.map { |i| clustered_data[i] })
+      expect(model.labels.size).to eq(clustered_data.size)
+      expect(model.cost).to be > 0
+
+      # One medoid per generating cluster, and every point labeled with its own cluster
+      expect(model.medoid_indices.map { |i| i / 30 }.sort).to eq([0, 1, 2])
+      clustered_data.each_index.group_by { |i| i / 30 }.each_value do |indices|
+        expect(indices.map { |i| model.labels[i] }.uniq.size).to eq(1)
+      end
+    end
+
+    it 'reports the total distance to the nearest medoid as cost' do
+      model = described_class.new(k: 3).fit(clustered_data)
+      expected = clustered_data.each_with_index.sum do |point, i|
+        medoid = model.medoids[model.labels[i]]
+        Math.sqrt(point.zip(medoid).sum { |a, b| (a - b)**2 })
+      end
+      expect(model.cost).to be_within(1e-9).of(expected)
+    end
+
+    it 'accepts a precomputed distance matrix' do
+      points = [[0.0], [1.0], [2.0], [10.0], [11.0], [12.0]]
+      distances = points.map { |a| points.map { |b| (a[0] - b[0]).abs } }
+      model = described_class.new(k: 2, metric: :precomputed).fit(distances)
+
+      expect(model.medoid_indices).to eq([1, 4])
+      expect(model.cost).to eq(4.0)
+      expect(model.medoids).to be_nil
+      expect { model.predict(points) }.to raise_error(ArgumentError, /precomputed/)
+    end
+
+    it 'rejects a non-square precomputed matrix' do
+      model = described_class.new(k: 2, metric: :precomputed)
+      expect { model.fit([[0.0, 1.0, 2.0], [1.0, 0.0, 3.0]]) }.to raise_error(ClusterKit::DimensionError, /square/)
+    end
+
+    it 'supports manhattan and cosine distances' do
+      %i[manhattan cosine].each do |metric|
+        model = described_class.new(k: 2, metric: metric).fit([[1.0, 0.0], [2.0, 0.1], [0.0, 1.0], [0.1, 2.0]])
+        expect(model.labels[0]).to eq(model.labels[1])
+        expect(model.labels[2]).to eq(model.labels[3])
+        expect(model.labels[0]).not_to eq(model.labels[2])
+      end
+    end
+
+    it 'finds the same clusters with CLARA' do
+      clara = described_class.new(k: 3, algorithm: :clara, sample_size: 20, random_seed: 42).fit(clustered_data)
+      expect(clara.medoid_indices.map { |i| i / 30 }.sort).to eq([0, 1, 2])
+      expect(clara.labels.size).to eq(clustered_data.size)
+    end
+
+    it 'is reproducible with a seed under CLARA' do
+      model1 = described_class.new(k: 3, algorithm: :clara, sample_size: 20, random_seed: 1).fit(clustered_data)
+      model2 = described_class.new(k: 3, algorithm: :clara, sample_size: 20, random_seed: 1).fit(clustered_data)
+      expect(model1.medoid_indices).to eq(model2.medoid_indices)
+    end
+
+    it 'rejects k larger than the number of samples' do
+      expect { described_class.new(k: 5).fit([[1.0, 2.0], [3.0, 4.0]]) }.to raise_error(ArgumentError)
+    end
+  end
+
+  describe '#predict' do
+    it 'assigns new points to the nearest medoid' do
+      model = described_class.new(k: 3).fit(clustered_data)
+      labels = model.predict([[0.5, 0.5], [10.5, 10.5], [20.5, 0.5]])
+      expect(labels).to eq([model.labels[0], model.labels[30], model.labels[60]])
+    end
+
+    it 'raises if not fitted' do
+      expect { described_class.new(k: 3).predict([[1.0, 2.0]]) }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
+    end
+  end
+end
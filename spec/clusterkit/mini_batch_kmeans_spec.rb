@@ -0,0 +1,134 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+require 'clusterkit/clustering/mini_batch_kmeans'
+
+RSpec.describe ClusterKit::Clustering::MiniBatchKMeans do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters
+    srand(2024)
+    cluster1 = 200.times.map { [rand, rand] }
+    cluster2 = 200.times.map { [rand + 10, rand + 10] }
+    cluster3 = 200.times.map { [rand + 20, rand] }
+    (cluster1 + cluster2 + cluster3).shuffle(random: Random.new(1))
+  }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new(k: 3)
+      expect(model.k).to eq(3)
+      expect(model.batch_size).to eq(1024)
+      expect(model.max_iter).to eq(100)
+      expect(model.tol).to eq(1e-4)
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(k: 0) }.to raise_error(ArgumentError, /k must be positive/)
+      expect { described_class.new(k: 3, batch_size: 0) }.to raise_error(ArgumentError, /batch_size/)
+      expect { described_class.new(k: 3, tol: -1.0) }.to raise_error(ArgumentError, /tol must be non-negative/)
+    end
+  end
+
+  describe '#fit' do
+    let(:model) { described_class.new(k: 3, batch_size: 64, max_iter: 50, random_seed: 42) }
+
+    it 'finds the clusters' do
+      model.fit(clustered_data)
+      expect(model).to be_fitted
+      expect(model.labels.size).to eq(clustered_data.size)
+      expect(model.labels.uniq.size).to eq(3)
+      expect(model.centroids.size).to eq(3)
+
+      # Each generating cluster should map to a single label
+      groups = clustered_data.each_index.group_by { |i| cluster_of(clustered_data[i]) }
+      groups.each_value do |indices|
+        expect(indices.map { |i| model.labels[i] }.uniq.size).to eq(1)
+      end
+    end
+
+    it 'reports counts and inertia' do
+      model.fit(clustered_data)
+      expect(model.counts.size).to eq(3)
+      expect(model.counts.sum).to be > 0
+      expect(model.inertia).to be_a(Float)
+    end
+
+    it 'stops once the centroids settle and reports the steps taken' do
+      model = described_class.new(k: 3, batch_size: 64, max_iter: 500, random_seed: 42).fit(clustered_data)
+      expect(model).to be_converged
+      expect(model.n_iter).to be_between(1, 499)
+    end
+
+    it 'runs every step with a zero tolerance' do
+      model = described_class.new(k: 3, batch_size: 64, max_iter: 30, tol: 0, random_seed: 42).fit(clustered_data)
+      expect(model).not_to be_converged
+      expect(model.n_iter).to eq(30)
+      expect(model.counts.sum).to eq(64 * 30)
+    end
+
+    it 'is reproducible with a seed' do
+      model1 = described_class.new(k: 3, batch_size: 64, max_iter: 20, random_seed: 7).fit(clustered_data)
+      model2 = described_class.new(k: 3, batch_size: 64, max_iter: 20, random_seed: 7).fit(clustered_data)
+      expect(model1.centroids).to eq(model2.centroids)
+      expect(model1.labels).to eq(model2.labels)
+    end
+  end
+
+  describe '#partial_fit' do
+    it 'learns incrementally from batches' do
+      model = described_class.new(k: 3, random_seed: 42)
+      clustered_data.each_slice(100) { |batch| model.partial_fit(batch) }
+
+      expect(model).to be_fitted
+      expect(model.counts.sum).to eq(clustered_data.size)
+
+      labels = model.predict([[0.5, 0.5], [10.5, 10.5], [20.5, 0.5]])
+      expect(labels.uniq.size).to eq(3)
+    end
+
+    it 'keeps each centroid at the mean of its assigned points' do
+      model = described_class.new(k: 1, random_seed: 1)
+      model.partial_fit([[0.0, 0.0], [2.0, 2.0]])
+      model.partial_fit([[4.0, 4.0], [6.0, 6.0]])
+
+      expect(model.counts).to eq([4.0])
+      expect(model.centroids.first[0]).to be_within(1e-10).of(3.0)
+      expect(model.centroids.first[1]).to be_within(1e-10).of(3.0)
+    end
+
+    it 'moves a centroid that never wins a point onto the farthest batch point' do
+      batch = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [4.0, 4.0]]
+      result = ClusterKit::Clustering.minibatch_partial_fit_rust(batch, [[0.5, 0.5], [100.0, 100.0]], [0.0, 0.0], 2, nil)
+
+      expect(result["centroids"][1]).to eq([4.0, 4.0])
+      expect(result["counts"]).to eq([3.0, 1.0])
+    end
+
+    it 'requires the first batch to contain at least k points' do
+      model = described_class.new(k: 5)
+      expect { model.partial_fit([[1.0, 2.0], [3.0, 4.0]]) }.to raise_error(ArgumentError, /at least k/)
+    end
+
+    it 'rejects batches with a different number of features' do
+      model = described_class.new(k: 2, random_seed: 1)
+      model.partial_fit([[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]])
+      expect { model.partial_fit([[1.0, 2.0, 3.0]]) }.to raise_error(ClusterKit::DimensionMismatchError)
+    end
+  end
+
+  describe '#predict' do
+    it 'raises if not fitted' do
+      expect { described_class.new(k: 3).predict([[1.0, 2.0]]) }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
+    end
+  end
+
+  def cluster_of(point)
+    # Recover the generating cluster from the point's position
+    if point[0] >= 20 then 2
+    elsif point[1] >= 10 then 1
+    else 0
+    end
+  end
+end
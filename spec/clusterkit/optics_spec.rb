@@ -0,0 +1,146 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::OPTICS do
+  let(:clustered_data) {
+    # Create 3 well-separated clusters plus far outliers
+    srand(11)
+    cluster1 = 20.times.map { [rand * 2, rand * 2] }
+    cluster2 = 20.times.map { [rand * 2 + 5, rand * 2 + 5] }
+    cluster3 = 20.times.map { [rand * 2 + 10, rand * 2] }
+    noise = [[20, 20], [-5, -5], [15, 15], [8, -3], [3, 10]]
+    cluster1 + cluster2 + cluster3 + noise
+  }
+
+  let(:blob_of) { ->(index) { index < 60 ? index / 20 : nil } }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new
+      expect(model.min_samples).to eq(5)
+      expect(model.max_eps).to eq(Float::INFINITY)
+      expect(model.cluster_method).to eq('xi')
+      expect(model.xi).to eq(0.05)
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(min_samples: 0) }.to raise_error(ArgumentError, /min_samples must be positive/)
+      expect { described_class.new(max_eps: 0) }.to raise_error(ArgumentError, /max_eps must be positive/)
+      expect { described_class.new(xi: 1.0) }.to raise_error(ArgumentError, /xi must be between 0 and 1/)
+      expect { described_class.new(metric: 'hamming') }.to raise_error(ArgumentError, /metric must be one of/)
+      expect { described_class.new(cluster_method: 'leaf') }.to raise_error(ArgumentError, /cluster_method/)
+    end
+
+    it 'needs a finite cut for the dbscan method' do
+      expect { described_class.new(cluster_method: 'dbscan') }.to raise_error(ArgumentError, /finite eps/)
+      expect {
+        described_class.new(cluster_method: 'dbscan', eps: 2.0, max_eps: 1.0)
+      }.to raise_error(ArgumentError, /must not exceed max_eps/)
+      expect { described_class.new(cluster_method: 'dbscan', max_eps: 1.0) }.not_to raise_error
+    end
+  end
+
+  describe '#fit' do
+    it 'returns an ordering with reachability, core distances and predecessors' do
+      model = described_class.new(min_samples: 4).fit(clustered_data)
+
+      expect(model).to be_fitted
+      expect(model.ordering.sort).to eq((0...clustered_data.size).to_a)
+      expect(model.reachability.size).to eq(clustered_data.size)
+      expect(model.core_distances.size).to eq(clustered_data.size)
+      expect(model.reachability[model.ordering.first]).to eq(Float::INFINITY)
+      expect(model.predecessor[model.ordering.first]).to eq(-1)
+      expect(model.core_sample_indices).to eq((0...clustered_data.size).to_a)
+    end
+
+    it 'keeps each cluster contiguous in the ordering' do
+      model = described_class.new(min_samples: 4).fit(clustered_data)
+      blobs = model.ordering.map { |index| blob_of.(index) }.compact
+      expect(blobs.chunk_while { |a, b| a == b }.map(&:first)).to eq(blobs.uniq)
+    end
+
+    it 'extracts xi clusters that stay within one dense region' do
+      model = described_class.new(min_samples: 4).fit(clustered_data)
+
+      expect(model.n_clusters).to be >= 3
+      expect(model.labels[60..64]).to all(eq(-1))
+      model.labels.each_with_index.reject { |label, _| label == -1 }.group_by(&:first).each_value do |members|
+        expect(members.map { |_, index| blob_of.(index) }.uniq.size).to eq(1)
+      end
+      model.cluster_hierarchy.each do |start, finish|
+        expect(start).to be <= finish
+      end
+    end
+
+    it 'cuts the reachability plot at eps with the dbscan method' do
+      model = described_class.new(min_samples: 4, cluster_method: 'dbscan', eps: 1.0).fit(clustered_data)
+
+      expect(model.n_clusters).to eq(3)
+      expect(model.labels[60..64]).to all(eq(-1))
+      expect(model.cluster_hierarchy).to be_nil
+      [0...20, 20...40, 40...60].each do |range|
+        expect(model.labels[range].reject { |l| l == -1 }.uniq.size).to eq(1)
+      end
+    end
+
+    it 'leaves every point unreachable when max_eps is too small' do
+      model = described_class.new(min_samples: 4, max_eps: 0.001).fit(clustered_data)
+
+      expect(model.core_distances).to all(eq(Float::INFINITY))
+      expect(model.core_sample_indices).to be_empty
+      expect(model.labels).to all(eq(-1))
+    end
+
+    it 'matches euclidean results on a precomputed distance matrix' do
+      distances = clustered_data.map do |a|
+        clustered_data.map { |b| Math.sqrt(a.zip(b).sum { |x, y| (x - y)**2 }) }
+      end
+      euclidean = described_class.new(min_samples: 4).fit(clustered_data)
+      precomputed = described_class.new(min_samples: 4, metric: 'precomputed').fit(distances)
+
+      expect(precomputed.ordering).to eq(euclidean.ordering)
+      expect(precomputed.labels).to eq(euclidean.labels)
+    end
+  end
+
+  describe '#extract_dbscan' do
+    it 'raises if not fitted' do
+      expect { described_class.new.extract_dbscan(1.0) }.to raise_error(ClusterKit::NotFittedError, /must be fitted/)
+    end
+
+    it 'labels the fitted plot at other radii without changing the model' do
+      model = described_class.new(min_samples: 4).fit(clustered_data)
+      labels = model.labels
+
+      tight = model.extract_dbscan(1.0)
+      expect(tight.uniq.reject { |l| l == -1 }.size).to eq(3)
+      expect(tight[60..64]).to all(eq(-1))
+
+      expect(model.extract_dbscan(100.0).uniq).to eq([0])
+      expect(model.labels).to eq(labels)
+    end
+
+    it 'agrees with DBSCAN on core points' do
+      model = described_class.new(min_samples: 4).fit(clustered_data)
+      dbscan = ClusterKit::Clustering::DBSCAN.new(eps: 1.0, min_samples: 4).fit(clustered_data)
+      optics_labels = model.extract_dbscan(1.0)
+
+      pairs = dbscan.core_sample_indices.map { |index| [dbscan.labels[index], optics_labels[index]] }.uniq
+      expect(pairs.map(&:first).uniq.size).to eq(pairs.size)
+      expect(pairs.map(&:last).uniq.size).to eq(pairs.size)
+    end
+  end
+
+  describe 'ClusterKit::Clustering.optics' do
+    it 'returns labels and the reachability plot' do
+      result = ClusterKit::Clustering.optics(clustered_data, min_samples: 4)
+      expect(result.keys).to contain_exactly(
+        :labels, :core_sample_indices, :ordering, :reachability, :core_distances,
+        :predecessor, :cluster_hierarchy, :n_clusters
+      )
+    end
+  end
+end
@@ -0,0 +1,95 @@
+# frozen_string_literal: true
+
+require 'spec_helper'
+require 'clusterkit/clustering'
+
+RSpec.describe ClusterKit::Clustering::SpectralClustering do
+  let(:rings) {
+    # Two concentric rings, which no centroid-based method can separate
+    srand(3)
+    [1.0, 4.0].flat_map do |radius|
+      60.times.map do |i|
+        angle = 2 * Math::PI * i / 60
+        [radius * Math.cos(angle) + 0.2 * rand, radius * Math.sin(angle) + 0.2 * rand]
+      end
+    end
+  }
+
+  describe '#initialize' do
+    it 'creates a new instance with defaults' do
+      model = described_class.new
+      expect(model.n_clusters).to eq(2)
+      expect(model.n_neighbors).to eq(10)
+      expect(model.metric).to eq('euclidean')
+      expect(model.n_init).to eq(10)
+      expect(model).not_to be_fitted
+    end
+
+    it 'validates parameters' do
+      expect { described_class.new(n_clusters: 0) }.to raise_error(ArgumentError, /n_clusters must be positive/)
+      expect { described_class.new(n_neighbors: 0) }.to raise_error(ArgumentError, /n_neighbors must be positive/)
+      expect { described_class.new(n_init: 0) }.to raise_error(ArgumentError, /n_init must be positive/)
+      expect { described_class.new(metric: 'hamming') }.to raise_error(ArgumentError, /metric must be one of/)
+    end
+  end
+
+  describe '#fit' do
+    it 'separates concentric rings' do
+      labels = described_class.new(n_clusters: 2, random_seed: 42).fit_predict(rings)
+
+      expect(labels[0...60].uniq.size).to eq(1)
+      expect(labels[60...120].uniq.size).to eq(1)
+      expect(labels[0]).not_to eq(labels[60])
+    end
+
+    it 'returns the spectral embedding and Laplacian eigenvalues' do
+      model = described_class.new(n_clusters: 2, random_seed: 42).fit(rings)
+
+      expect(model).to be_fitted
+      expect(model).to be_converged
+      expect(model.embedding.size).to eq(rings.size)
+      model.embedding.each do |row|
+        expect(row.size).to eq(2)
+        expect(Math.sqrt(row.sum { |x| x * x })).to be_within(1e-6).of(1.0)
+      end
+
+      # Two rings the neighbor graph leaves unconnected give two zero eigenvalues
+      expect(model.eigenvalues.size).to eq(2)
+      expect(model.eigenvalues).to eq(model.eigenvalues.sort)
+      model.eigenvalues.each { |value| expect(value).to be_within(1e-3).of(0.0) }
+    end
+
+    it 'separates concentric rings given as a precomputed distance matrix' do
+      distances = rings.map do |a|
+        rings.map { |b| Math.sqrt(a.zip(b).sum { |x, y| (x - y) * (x - y) }) }
+      end
+      labels = described_class.new(n_clusters: 2, metric: 'precomputed', random_seed: 42).fit_predict(distances)
+
+      expect(labels[0...60].uniq.size).to eq(1)
+      expect(labels[60...120].uniq.size).to eq(1)
+      expect(labels[0]).not_to eq(labels[60])
+    end
+
+    it 'is reproducible with a random seed' do
+      first = described_class.new(n_clusters: 2, random_seed: 7).fit_predict(rings)
+      second = described_class.new(n_clusters: 2, random_seed: 7).fit_predict(rings)
+      expect(first).to eq(second)
+    end
+
+    it 'rejects n_neighbors that are not below the number of samples' do
+      model = described_class.new(n_neighbors: 5)
+      expect { model.fit(rings.first(5)) }.to raise_error(ArgumentError, /n_neighbors/)
+    end
+  end
+
+  describe '.spectral' do
+    it 'returns labels, embedding and eigenvalues' do
+      result = ClusterKit::Clustering.spectral(rings, n_clusters: 2, random_seed: 42)
+
+      expect(result[:labels].size).to eq(rings.size)
+      expect(result[:labels].uniq.sort).to eq([0, 1])
+      expect(result[:embedding].size).to eq(rings.size)
+      expect(result[:eigenvalues].size).to eq(2)
+    end
+  end
+end